@@ -0,0 +1,183 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use fusion_imu::{
+    Ahrs, Complementary, Convention, Eskf, EskfSettings, FusionAhrs, LogHeader, Madgwick, Mahony,
+    Settings,
+};
+use serde::{Deserialize, Serialize};
+
+/// Processing configuration, read from a TOML file and overridden by
+/// command-line flags.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Sample rate in Hz used by the gyroscope offset correction.
+    pub sample_rate: u32,
+    /// Whether the gyroscope offset is corrected at runtime.
+    pub offset_correction: bool,
+    /// Whether magnetometer columns are used if present.
+    pub magnetometer: bool,
+    /// AHRS algorithm.
+    pub filter: Filter,
+    /// AHRS algorithm settings, used by the Fusion algorithm only.
+    pub settings: Settings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            sample_rate: 100,
+            offset_correction: true,
+            magnetometer: true,
+            filter: Filter::default(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from a TOML file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+        let config =
+            toml::from_str(&text).map_err(|error| format!("{}: {error}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Returns an error if the filter does not support the convention of the
+    /// settings.
+    pub fn check(&self) -> Result<(), Box<dyn Error>> {
+        let name = match self.filter {
+            Filter::Madgwick { .. } => "Madgwick",
+            Filter::Mahony { .. } => "Mahony",
+            Filter::Complementary { .. } => "complementary",
+            Filter::Fusion | Filter::Eskf(_) => return Ok(()),
+        };
+        match self.settings.convention() {
+            Convention::NorthWestUp => Ok(()),
+            convention => Err(format!(
+                "the {name} filter only supports the NorthWestUp convention, not {convention:?}"
+            )
+            .into()),
+        }
+    }
+}
+
+impl From<&LogHeader> for Config {
+    fn from(header: &LogHeader) -> Self {
+        Self {
+            sample_rate: header.sample_rate,
+            offset_correction: header.offset_correction,
+            magnetometer: true,
+            filter: Filter::Fusion,
+            settings: header.settings,
+        }
+    }
+}
+
+/// AHRS algorithm and its parameters. The Madgwick, Mahony, and
+/// complementary filters use the North-West-Up convention.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Filter {
+    /// Fusion, configured by the settings.
+    #[default]
+    Fusion,
+    /// Madgwick's gradient descent algorithm.
+    Madgwick {
+        /// Gradient descent step size in radians per second.
+        #[serde(default = "default_beta")]
+        beta: f32,
+    },
+    /// Mahony's nonlinear complementary filter.
+    Mahony {
+        /// Proportional gain.
+        #[serde(default = "default_kp")]
+        kp: f32,
+        /// Integral gain.
+        #[serde(default)]
+        ki: f32,
+    },
+    /// Complementary filter.
+    Complementary {
+        /// Time constant in seconds.
+        #[serde(default = "default_time_constant")]
+        time_constant: f32,
+    },
+    /// Error-state Kalman filter.
+    Eskf(EskfSettings),
+}
+
+impl Filter {
+    /// Create a new AHRS algorithm.
+    pub fn build(&self, settings: Settings) -> Box<dyn Ahrs> {
+        match *self {
+            Filter::Fusion => {
+                let mut ahrs = FusionAhrs::new();
+                ahrs.set_settings(settings);
+                Box::new(ahrs)
+            }
+            Filter::Madgwick { beta } => Box::new(Madgwick::new(beta)),
+            Filter::Mahony { kp, ki } => Box::new(Mahony::new(kp, ki)),
+            Filter::Complementary { time_constant } => Box::new(Complementary::new(time_constant)),
+            Filter::Eskf(settings) => Box::new(Eskf::new(settings)),
+        }
+    }
+}
+
+fn default_beta() -> f32 {
+    Madgwick::default().beta
+}
+
+fn default_kp() -> f32 {
+    Mahony::default().kp
+}
+
+fn default_time_constant() -> f32 {
+    Complementary::default().time_constant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file_uses_defaults() {
+        let text = "
+sample_rate = 400
+
+[settings]
+convention = \"EastNorthUp\"
+gain = 0.2
+";
+
+        // Act
+        let config: Config = toml::from_str(text).unwrap();
+
+        assert_eq!(config.sample_rate, 400);
+        assert!(config.offset_correction);
+        assert_eq!(config.settings.convention(), Convention::EastNorthUp);
+        assert_eq!(config.settings.gain(), 0.2);
+        assert_eq!(
+            config.settings.acceleration_rejection(),
+            Settings::default().acceleration_rejection()
+        );
+    }
+
+    #[test]
+    fn filter_parameters_use_defaults() {
+        let text = "
+[filter]
+type = \"mahony\"
+ki = 0.1
+";
+
+        // Act
+        let config: Config = toml::from_str(text).unwrap();
+
+        assert_eq!(config.filter, Filter::Mahony { kp: 0.5, ki: 0.1 });
+    }
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+
+use fusion_imu::io::Sample;
+use fusion_imu::{Ahrs, FusionOffset, Vector};
+
+use crate::config::Config;
+
+/// Runs the samples through the gyroscope offset correction and the AHRS
+/// algorithm selected by the configuration. The observer is called after
+/// each update with the sample, the offset-corrected gyroscope measurement,
+/// and the AHRS algorithm. Returns the number of samples, or an error if the
+/// time of a sample is not finite or not after the time of the previous
+/// sample.
+///
+/// The update of each sample depends on the available measurements:
+/// - Heading: `update_external_heading`.
+/// - Magnetometer: `update`, unless disabled in the configuration.
+/// - Otherwise: `update_no_magnetometer`.
+pub fn fuse<E, F>(
+    samples: impl IntoIterator<Item = Result<Sample, E>>,
+    config: &Config,
+    mut observer: F,
+) -> Result<usize, Box<dyn Error>>
+where
+    E: Into<Box<dyn Error>>,
+    F: FnMut(&Sample, Vector, &dyn Ahrs) -> Result<(), Box<dyn Error>>,
+{
+    let mut ahrs = config.filter.build(config.settings);
+    let mut offset = FusionOffset::new(config.sample_rate);
+    let mut previous_time = None;
+    let mut count = 0;
+
+    for sample in samples {
+        let sample = sample.map_err(Into::into)?;
+        if !sample.time.is_finite() {
+            return Err(format!("sample {}: invalid time {}", count + 1, sample.time).into());
+        }
+        let delta_time = match previous_time {
+            Some(previous) if sample.time <= previous => {
+                return Err(format!(
+                    "sample {}: time {} is not after the previous time {previous}",
+                    count + 1,
+                    sample.time
+                )
+                .into())
+            }
+            Some(previous) => (sample.time - previous) as f32,
+            None => 1.0 / config.sample_rate as f32,
+        };
+        previous_time = Some(sample.time);
+
+        let gyroscope = if config.offset_correction {
+            offset.update(sample.gyroscope)
+        } else {
+            sample.gyroscope
+        };
+        match (sample.heading, sample.magnetometer) {
+            (Some(heading), _) => {
+                ahrs.update_external_heading(gyroscope, sample.accelerometer, heading, delta_time)
+            }
+            (None, Some(magnetometer)) if config.magnetometer => {
+                ahrs.update(gyroscope, sample.accelerometer, magnetometer, delta_time)
+            }
+            _ => ahrs.update_no_magnetometer(gyroscope, sample.accelerometer, delta_time),
+        }
+
+        observer(&sample, gyroscope, ahrs.as_ref())?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    #[test]
+    fn external_heading_is_used() {
+        let samples = (0..500).map(|index| {
+            Ok::<_, Infallible>(Sample {
+                time: index as f64 * 0.01,
+                accelerometer: Vector::new(0.0, 0.0, 1.0),
+                heading: Some(45.0),
+                ..Default::default()
+            })
+        });
+        let mut yaw = 0.0;
+
+        // Act
+        let count = fuse(samples, &Config::default(), |_, _, ahrs| {
+            yaw = ahrs.quaternion().to_euler().yaw;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, 500);
+        assert!((yaw - 45.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn non_increasing_time_is_rejected() {
+        let samples = [0.0, 0.01, 0.01].map(|time| {
+            Ok::<_, Infallible>(Sample {
+                time,
+                accelerometer: Vector::new(0.0, 0.0, 1.0),
+                ..Default::default()
+            })
+        });
+        let mut count = 0;
+
+        // Act
+        let result = fuse(samples, &Config::default(), |_, _, _| {
+            count += 1;
+            Ok(())
+        });
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "sample 3: time 0.01 is not after the previous time 0.01"
+        );
+    }
+
+    #[test]
+    fn non_finite_time_is_rejected() {
+        let samples = [0.0, f64::NAN, 0.01].map(|time| {
+            Ok::<_, Infallible>(Sample {
+                time,
+                accelerometer: Vector::new(0.0, 0.0, 1.0),
+                ..Default::default()
+            })
+        });
+
+        // Act
+        let result = fuse(samples, &Config::default(), |_, _, _| Ok(()));
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "sample 2: invalid time NaN"
+        );
+    }
+}
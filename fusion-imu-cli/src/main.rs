@@ -0,0 +1,373 @@
+//! Command-line tool to fuse recorded IMU logs.
+
+mod config;
+mod fuse;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use fusion_imu::io::csv::{CsvReader, CsvWriter};
+use fusion_imu::io::Sample;
+use fusion_imu::plot::Recorder;
+use fusion_imu::{Complementary, Convention, EskfSettings, LogReader, Madgwick, Mahony};
+
+use crate::config::{Config, Filter};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fuse a recorded IMU log and write the orientation stream as CSV.
+    Fuse(FuseArgs),
+    /// Fuse a recorded IMU log and plot the measurements and outputs as SVG.
+    Plot(PlotArgs),
+}
+
+#[derive(Args)]
+struct FuseArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Output CSV file. Defaults to standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct PlotArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Output SVG file.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+/// Input log and processing configuration.
+#[derive(Args)]
+struct InputArgs {
+    /// Input CSV log with time, gyroscope, accelerometer, and optional
+    /// magnetometer and heading columns, or a binary log.
+    input: PathBuf,
+    /// TOML configuration file.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    #[command(flatten)]
+    overrides: Overrides,
+}
+
+impl InputArgs {
+    /// Opens the input log and returns its samples and the configuration.
+    /// The configuration starts from the header of a binary log, or the
+    /// defaults for CSV, and is replaced by the configuration file if given.
+    /// The overrides are applied last.
+    fn open(&self) -> Result<(Samples, Config), Box<dyn Error>> {
+        let context = |error: &dyn Error| format!("{}: {error}", self.input.display());
+        let file = File::open(&self.input).map_err(|error| context(&error))?;
+        let mut file = BufReader::new(file);
+        let binary = file
+            .fill_buf()
+            .map_err(|error| context(&error))?
+            .starts_with(b"FIMU");
+
+        let (samples, mut config): (Samples, _) = if binary {
+            let reader = LogReader::new(file).map_err(|error| context(&error))?;
+            let config = Config::from(reader.header());
+            let samples = reader
+                .samples()
+                .map(move |record| record.map(Sample::from).map_err(Into::into));
+            (Box::new(samples), config)
+        } else {
+            let reader = CsvReader::new(file).map_err(|error| context(&error))?;
+            let samples = reader.map(|sample| sample.map_err(Into::into));
+            (Box::new(samples), Config::default())
+        };
+        if let Some(path) = &self.config {
+            config = Config::load(path)?;
+        }
+        self.overrides.apply(&mut config)?;
+        Ok((samples, config))
+    }
+}
+
+/// Samples of an input log.
+type Samples = Box<dyn Iterator<Item = Result<Sample, Box<dyn Error>>>>;
+
+/// Configuration values that override the configuration file.
+#[derive(Args)]
+struct Overrides {
+    /// Sample rate in Hz used by the gyroscope offset correction and the
+    /// ESKF.
+    #[arg(long)]
+    sample_rate: Option<u32>,
+    /// AHRS algorithm, with the parameters of the configuration file or the
+    /// defaults.
+    #[arg(long, value_enum)]
+    filter: Option<FilterArg>,
+    /// Earth axes convention, also used by the ESKF.
+    #[arg(long, value_enum)]
+    convention: Option<ConventionArg>,
+    /// AHRS algorithm gain.
+    #[arg(long)]
+    gain: Option<f32>,
+    /// Gyroscope range in degrees per second.
+    #[arg(long)]
+    gyroscope_range: Option<f32>,
+    /// Acceleration rejection in degrees.
+    #[arg(long)]
+    acceleration_rejection: Option<f32>,
+    /// Magnetic rejection in degrees.
+    #[arg(long)]
+    magnetic_rejection: Option<f32>,
+    /// Recovery trigger period in samples.
+    #[arg(long)]
+    recovery_trigger_period: Option<u32>,
+    /// Disable the runtime gyroscope offset correction.
+    #[arg(long)]
+    no_offset_correction: bool,
+    /// Ignore magnetometer columns.
+    #[arg(long)]
+    no_magnetometer: bool,
+}
+
+impl Overrides {
+    /// Applies the overrides and returns an error if the resulting filter
+    /// does not support the convention.
+    fn apply(&self, config: &mut Config) -> Result<(), Box<dyn Error>> {
+        if let Some(sample_rate) = self.sample_rate {
+            config.sample_rate = sample_rate;
+        }
+        if let Some(filter) = self.filter.map(Filter::from) {
+            // Keep the parameters of the configuration file for the same type
+            if mem::discriminant(&filter) != mem::discriminant(&config.filter) {
+                config.filter = filter;
+                // A new ESKF follows the convention and sample rate of the input
+                if let Filter::Eskf(settings) = &mut config.filter {
+                    settings.convention = config.settings.convention();
+                    settings.sample_rate = config.sample_rate as f32;
+                }
+            }
+        }
+        if let Some(convention) = self.convention {
+            config.settings.set_convention(convention.into());
+        }
+        if let Filter::Eskf(settings) = &mut config.filter {
+            if let Some(convention) = self.convention {
+                settings.convention = convention.into();
+            }
+            if let Some(sample_rate) = self.sample_rate {
+                settings.sample_rate = sample_rate as f32;
+            }
+        }
+        if let Some(gain) = self.gain {
+            config.settings.set_gain(gain);
+        }
+        if let Some(range) = self.gyroscope_range {
+            config.settings.set_gyroscope_range(range);
+        }
+        if let Some(rejection) = self.acceleration_rejection {
+            config.settings.set_acceleration_rejection(rejection);
+        }
+        if let Some(rejection) = self.magnetic_rejection {
+            config.settings.set_magnetic_rejection(rejection);
+        }
+        if let Some(period) = self.recovery_trigger_period {
+            config.settings.set_recovery_trigger_period(period);
+        }
+        if self.no_offset_correction {
+            config.offset_correction = false;
+        }
+        if self.no_magnetometer {
+            config.magnetometer = false;
+        }
+        config.check()
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FilterArg {
+    /// Fusion.
+    Fusion,
+    /// Madgwick.
+    Madgwick,
+    /// Mahony.
+    Mahony,
+    /// Complementary filter.
+    Complementary,
+    /// Error-state Kalman filter.
+    Eskf,
+}
+
+impl From<FilterArg> for Filter {
+    fn from(filter: FilterArg) -> Self {
+        match filter {
+            FilterArg::Fusion => Filter::Fusion,
+            FilterArg::Madgwick => Filter::Madgwick {
+                beta: Madgwick::default().beta,
+            },
+            FilterArg::Mahony => {
+                let Mahony { kp, ki, .. } = Mahony::default();
+                Filter::Mahony { kp, ki }
+            }
+            FilterArg::Complementary => Filter::Complementary {
+                time_constant: Complementary::default().time_constant,
+            },
+            FilterArg::Eskf => Filter::Eskf(EskfSettings::default()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ConventionArg {
+    /// North-West-Up.
+    Nwu,
+    /// East-North-Up.
+    Enu,
+    /// North-East-Down.
+    Ned,
+}
+
+impl From<ConventionArg> for Convention {
+    fn from(convention: ConventionArg) -> Self {
+        match convention {
+            ConventionArg::Nwu => Convention::NorthWestUp,
+            ConventionArg::Enu => Convention::EastNorthUp,
+            ConventionArg::Ned => Convention::NorthWestDown,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Fuse(args) => run_fuse(args),
+        Command::Plot(args) => run_plot(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_fuse(args: FuseArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, config) = args.input.open()?;
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path).map_err(|error| context(path, error))?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = CsvWriter::new(BufWriter::new(output))?;
+
+    let count = fuse::fuse(samples, &config, |sample, _, ahrs| {
+        writer.write(sample.time, ahrs)?;
+        Ok(())
+    })?;
+    writer.into_inner()?;
+    if args.output.is_some() {
+        eprintln!("processed {count} samples");
+    }
+    Ok(())
+}
+
+fn run_plot(args: PlotArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, config) = args.input.open()?;
+    let mut recorder = Recorder::new();
+
+    fuse::fuse(samples, &config, |sample, gyroscope, ahrs| {
+        recorder.record(sample.time as f32, gyroscope, sample.accelerometer, ahrs);
+        Ok(())
+    })?;
+    recorder
+        .plot()
+        .save(&args.output)
+        .map_err(|error| context(&args.output, error))?;
+    eprintln!("plotted {} samples", recorder.len());
+    Ok(())
+}
+
+/// Adds the path to an error message.
+fn context(path: &Path, error: io::Error) -> String {
+    format!("{}: {error}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use fusion_imu::Vector;
+
+    use super::*;
+
+    fn overrides(args: &[&str]) -> Overrides {
+        let args = ["fusion-imu", "fuse", "input.csv"].iter().chain(args);
+        match Cli::try_parse_from(args).unwrap().command {
+            Command::Fuse(args) => args.input.overrides,
+            Command::Plot(args) => args.input.overrides,
+        }
+    }
+
+    #[test]
+    fn eskf_follows_convention_and_sample_rate() {
+        let mut config = Config::default();
+        let samples = (0..100).map(|index| {
+            Ok::<_, Infallible>(Sample {
+                time: index as f64 * 0.0025,
+                accelerometer: Vector::new(0.0, 0.0, -1.0),
+                ..Default::default()
+            })
+        });
+
+        // Act
+        overrides(&[
+            "--filter",
+            "eskf",
+            "--convention",
+            "ned",
+            "--sample-rate",
+            "400",
+        ])
+        .apply(&mut config)
+        .unwrap();
+
+        let Filter::Eskf(settings) = config.filter else {
+            panic!("expected ESKF");
+        };
+        assert_eq!(settings.convention, Convention::NorthWestDown);
+        assert_eq!(settings.sample_rate, 400.0);
+        let mut roll = f32::NAN;
+        fuse::fuse(samples, &config, |_, _, ahrs| {
+            roll = ahrs.quaternion().to_euler().roll;
+            Ok(())
+        })
+        .unwrap();
+        assert!(roll.abs() < 1.0);
+    }
+
+    #[test]
+    fn nwu_only_filter_rejects_other_conventions() {
+        let mut config = Config::default();
+
+        // Act
+        let result = overrides(&["--filter", "madgwick", "--convention", "enu"]).apply(&mut config);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "the Madgwick filter only supports the NorthWestUp convention, not EastNorthUp"
+        );
+        let mut config = Config::default();
+        assert!(overrides(&["--filter", "mahony", "--convention", "nwu"])
+            .apply(&mut config)
+            .is_ok());
+    }
+}
@@ -4,11 +4,103 @@ use fusion_imu_sys as sys;
 
 use crate::math::{Quaternion, Vector};
 use crate::settings::Settings;
-use crate::{Flags, InternalStates};
+use crate::{
+    magnetic_to_true, yaw_to_heading, Convention, Flags, HeadingUnwrapper, InternalStates, Tilt,
+};
+
+/// Common interface of AHRS algorithms.
+///
+/// Measurement units follow [`FusionAhrs`]: degrees per second, g, and
+/// arbitrary magnetometer units.
+pub trait Ahrs {
+    /// Updates the algorithm using the gyroscope, accelerometer, and
+    /// magnetometer measurements.
+    ///
+    /// Arguments:
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `magnetometer`: Magnetometer measurement in arbitrary units.
+    /// - `delta_time`: Delta time in seconds.
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    );
+
+    /// Updates the algorithm using the gyroscope and accelerometer
+    /// measurements only.
+    ///
+    /// Arguments:
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `delta_time`: Delta time in seconds.
+    fn update_no_magnetometer(&mut self, gyroscope: Vector, accelerometer: Vector, delta_time: f32);
+
+    /// Updates the algorithm using the gyroscope, accelerometer, and heading
+    /// measurements.
+    ///
+    /// The default implementation synthesises a magnetometer measurement in
+    /// the North-West-Up convention from the heading and the current roll, as
+    /// Fusion does, and calls [`update`](Ahrs::update).
+    ///
+    /// Arguments:
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `heading`: Heading measurement in degrees.
+    /// - `delta_time`: Delta time in seconds.
+    fn update_external_heading(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        heading: f32,
+        delta_time: f32,
+    ) {
+        let q = self.quaternion();
+        let roll = libm::atan2f(q.w * q.x + q.y * q.z, 0.5 - q.y * q.y - q.x * q.x);
+        let heading = heading.to_radians();
+        let magnetometer = Vector::new(
+            libm::cosf(heading),
+            -libm::cosf(roll) * libm::sinf(heading),
+            libm::sinf(heading) * libm::sinf(roll),
+        );
+        self.update(gyroscope, accelerometer, magnetometer, delta_time);
+    }
+
+    /// Returns the quaternion describing the sensor relative to the Earth.
+    fn quaternion(&self) -> Quaternion;
+
+    /// Returns the linear acceleration measurement equal to the accelerometer
+    /// measurement with the 1g of gravity removed.
+    fn linear_acceleration(&self) -> Vector;
+
+    /// Returns the Earth acceleration measurement equal to the accelerometer
+    /// measurement in the Earth coordinate frame with the 1g of gravity
+    /// removed.
+    fn earth_acceleration(&self) -> Vector;
+
+    /// Resets the algorithm while maintaining its settings.
+    fn reset(&mut self);
+
+    /// Returns the algorithm flags. The default implementation returns all
+    /// flags cleared.
+    fn flags(&self) -> Flags {
+        Flags::default()
+    }
+
+    /// Returns the algorithm internal states. The default implementation
+    /// returns all states zero.
+    fn internal_states(&self) -> InternalStates {
+        InternalStates::default()
+    }
+}
 
 /// AHRS algorithm structure.
 pub struct FusionAhrs {
     inner: sys::FusionAhrs,
+    declination: f32,
+    yaw: Option<HeadingUnwrapper>,
 }
 
 impl FusionAhrs {
@@ -19,6 +111,8 @@ impl FusionAhrs {
             sys::FusionAhrsInitialise(ahrs.as_mut_ptr());
             FusionAhrs {
                 inner: ahrs.assume_init(),
+                declination: 0.0,
+                yaw: None,
             }
         }
     }
@@ -39,6 +133,9 @@ impl FusionAhrs {
         unsafe {
             sys::FusionAhrsReset(&mut self.inner as *mut sys::FusionAhrs);
         }
+        if let Some(yaw) = &mut self.yaw {
+            yaw.reset();
+        }
     }
 
     /// Updates the AHRS algorithm using the gyroscope, accelerometer, and
@@ -65,6 +162,7 @@ impl FusionAhrs {
                 delta_time,
             )
         }
+        self.update_yaw();
     }
 
     /// Updates the AHRS algorithm using the gyroscope and accelerometer
@@ -88,6 +186,7 @@ impl FusionAhrs {
                 delta_time,
             )
         }
+        self.update_yaw();
     }
 
     /// Updates the AHRS algorithm using the gyroscope, accelerometer, and
@@ -114,6 +213,7 @@ impl FusionAhrs {
                 delta_time,
             )
         }
+        self.update_yaw();
     }
 
     /// Sets the heading of the orientation measurement provided by the AHRS
@@ -125,6 +225,7 @@ impl FusionAhrs {
         unsafe {
             sys::FusionAhrsSetHeading(&mut self.inner as *mut sys::FusionAhrs, heading);
         }
+        self.update_yaw();
     }
 
     /// Returns the quaternion describing the sensor relative to the Earth.
@@ -137,6 +238,7 @@ impl FusionAhrs {
         unsafe {
             sys::FusionAhrsSetQuaternion(&mut self.inner as *mut sys::FusionAhrs, quaternion.into())
         }
+        self.update_yaw();
     }
 
     /// Returns the linear acceleration measurement equal to the accelerometer
@@ -154,6 +256,63 @@ impl FusionAhrs {
         unsafe { sys::FusionAhrsGetEarthAcceleration(&self.inner as *const sys::FusionAhrs).into() }
     }
 
+    /// Returns the accelerometer measurement of gravity in the sensor frame,
+    /// equal to the accelerometer measurement with the linear acceleration
+    /// removed.
+    pub fn get_gravity(&self) -> Vector {
+        let gravity = self.get_quaternion().to_matrix().rows()[2];
+        match self.convention() {
+            Convention::NorthWestDown => -gravity,
+            _ => gravity,
+        }
+    }
+
+    /// Returns the tilt of the sensor relative to level, independent of
+    /// heading.
+    pub fn get_tilt(&self) -> Tilt {
+        Tilt::from_gravity(self.get_gravity(), self.convention())
+    }
+
+    /// Returns the quaternion describing the sensor relative to the Earth with
+    /// the heading removed, so that only the tilt remains.
+    pub fn get_level_quaternion(&self) -> Quaternion {
+        let quaternion = self.get_quaternion();
+        let heading = quaternion.twist(Vector::new(0.0, 0.0, 1.0));
+        heading.conjugate() * quaternion
+    }
+
+    /// Enables or disables the tracking of the continuous yaw, which costs an
+    /// Euler angle conversion per update. Enabling while already enabled
+    /// keeps the accumulated turns.
+    pub fn set_continuous_yaw(&mut self, enabled: bool) {
+        if !enabled {
+            self.yaw = None;
+        } else if self.yaw.is_none() {
+            self.yaw = Some(HeadingUnwrapper::new());
+            self.update_yaw();
+        }
+    }
+
+    /// Returns the yaw angle in degrees, continuous across turns rather than
+    /// wrapped at ±180 degrees, or `None` if the tracking is not enabled by
+    /// [`set_continuous_yaw`](FusionAhrs::set_continuous_yaw).
+    pub fn get_continuous_yaw(&self) -> Option<f32> {
+        self.yaw.map(|yaw| yaw.continuous())
+    }
+
+    /// Sets the magnetic declination in degrees, positive east, used to
+    /// calculate the true heading. Defaults to zero.
+    pub fn set_declination(&mut self, declination: f32) {
+        self.declination = declination;
+    }
+
+    /// Returns the true heading in degrees clockwise from north in the range
+    /// [0, 360), assuming that the heading is referenced to magnetic north.
+    pub fn get_true_heading(&self) -> f32 {
+        let yaw = self.get_quaternion().to_euler().yaw;
+        magnetic_to_true(yaw_to_heading(yaw, self.convention()), self.declination)
+    }
+
     /// Returns the AHRS algorithm internal states.
     pub fn get_internal_states(&self) -> InternalStates {
         unsafe {
@@ -171,8 +330,79 @@ impl FusionAhrs {
     }
 }
 
+impl FusionAhrs {
+    fn convention(&self) -> Convention {
+        Settings {
+            inner: self.inner.settings,
+        }
+        .convention()
+    }
+
+    fn update_yaw(&mut self) {
+        if let Some(mut yaw) = self.yaw {
+            yaw.update(self.get_quaternion().to_euler().yaw);
+            self.yaw = Some(yaw);
+        }
+    }
+}
+
 impl Default for FusionAhrs {
     fn default() -> Self {
         Self::new()
     }
 }
+
+impl Ahrs for FusionAhrs {
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    ) {
+        FusionAhrs::update(self, gyroscope, accelerometer, magnetometer, delta_time)
+    }
+
+    fn update_no_magnetometer(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        delta_time: f32,
+    ) {
+        FusionAhrs::update_no_magnetometer(self, gyroscope, accelerometer, delta_time)
+    }
+
+    fn update_external_heading(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        heading: f32,
+        delta_time: f32,
+    ) {
+        FusionAhrs::update_external_heading(self, gyroscope, accelerometer, heading, delta_time)
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        self.get_quaternion()
+    }
+
+    fn linear_acceleration(&self) -> Vector {
+        self.get_linear_acceleration()
+    }
+
+    fn earth_acceleration(&self) -> Vector {
+        self.get_earth_acceleration()
+    }
+
+    fn reset(&mut self) {
+        FusionAhrs::reset(self)
+    }
+
+    fn flags(&self) -> Flags {
+        self.get_flags()
+    }
+
+    fn internal_states(&self) -> InternalStates {
+        self.get_internal_states()
+    }
+}
@@ -0,0 +1,319 @@
+use core::fmt;
+
+use crate::{Convention, InertialCalibration, Matrix, Quaternion, Vector};
+
+/// Minimum angle between the level and second attitude.
+const MINIMUM_ROTATION: f32 = 5.0;
+
+/// Minimum forward acceleration in g.
+const MINIMUM_ACCELERATION: f32 = 0.05;
+
+/// Estimates the fixed rotation from the sensor frame to the vehicle body
+/// frame.
+///
+/// The vehicle must be held still in a level attitude, and in a nose-up or
+/// rolled-right attitude. Alternatively, or additionally, accelerometer
+/// measurements from a straight forward acceleration can be used. The level
+/// attitude determines roll and pitch of the mounting while the second
+/// attitude or the forward acceleration determines yaw. If both are
+/// available, the forward acceleration is used.
+///
+/// Body axes follow the Earth axes convention:
+/// - North-West-Up: X forward, Y left, Z up.
+/// - East-North-Up: X right, Y forward, Z up.
+/// - North-East-Down: X forward, Y right, Z down.
+#[derive(Debug, Clone)]
+pub struct AlignmentEstimator {
+    convention: Convention,
+    level: Mean,
+    nose_up: Mean,
+    rolled_right: Mean,
+    forward: Mean,
+}
+
+impl AlignmentEstimator {
+    /// Create a new `AlignmentEstimator` instance.
+    pub fn new(convention: Convention) -> Self {
+        Self {
+            convention,
+            level: Mean::default(),
+            nose_up: Mean::default(),
+            rolled_right: Mean::default(),
+            forward: Mean::default(),
+        }
+    }
+
+    /// Adds an accelerometer measurement in g made while the vehicle is
+    /// stationary and level.
+    pub fn add_level_sample(&mut self, accelerometer: Vector) {
+        self.level.add(accelerometer);
+    }
+
+    /// Adds an accelerometer measurement in g made while the vehicle is
+    /// stationary and pitched nose-up.
+    pub fn add_nose_up_sample(&mut self, accelerometer: Vector) {
+        self.nose_up.add(accelerometer);
+    }
+
+    /// Adds an accelerometer measurement in g made while the vehicle is
+    /// stationary and rolled right wing down.
+    pub fn add_rolled_right_sample(&mut self, accelerometer: Vector) {
+        self.rolled_right.add(accelerometer);
+    }
+
+    /// Adds an accelerometer measurement in g made while the level vehicle is
+    /// accelerating straight forward.
+    pub fn add_forward_acceleration_sample(&mut self, accelerometer: Vector) {
+        self.forward.add(accelerometer);
+    }
+
+    /// Estimates the alignment from the samples.
+    pub fn estimate(&self) -> Result<SensorAlignment, AlignmentError> {
+        let level = self.level.mean().ok_or(AlignmentError::MissingLevel)?;
+        let axes = BodyAxes::new(self.convention);
+
+        let (sensor_secondary, body_secondary) = if let Some(forward) = self.forward.mean() {
+            let acceleration = forward - level;
+            // Only the horizontal component of the acceleration is used
+            if acceleration.cross(level.normalise()).magnitude() < MINIMUM_ACCELERATION {
+                return Err(AlignmentError::InsufficientAcceleration);
+            }
+            (acceleration, axes.forward)
+        } else {
+            let (second, body_axis) = match (self.nose_up.mean(), self.rolled_right.mean()) {
+                (Some(nose_up), _) => (nose_up, axes.nose_up),
+                (None, Some(rolled_right)) => (rolled_right, axes.rolled_right),
+                (None, None) => return Err(AlignmentError::MissingSecondAttitude),
+            };
+            let axis = level.cross(second);
+            let sine = axis.magnitude() / (level.magnitude() * second.magnitude());
+            if sine.is_nan() || sine < libm::sinf(MINIMUM_ROTATION.to_radians()) {
+                return Err(AlignmentError::InsufficientRotation);
+            }
+            (axis, body_axis)
+        };
+
+        let sensor = triad(level, sensor_secondary);
+        let body = triad(axes.up, body_secondary);
+        let matrix = body * sensor.transpose();
+        Ok(SensorAlignment {
+            quaternion: Quaternion::from_matrix(matrix),
+        })
+    }
+}
+
+/// Returns the orthonormal frame (as columns) defined by a primary and a
+/// secondary vector.
+fn triad(primary: Vector, secondary: Vector) -> Matrix {
+    let first = primary.normalise();
+    let second = primary.cross(secondary).normalise();
+    Matrix::from_columns(first, second, first.cross(second))
+}
+
+/// Body frame directions used by the alignment.
+struct BodyAxes {
+    /// Accelerometer measurement direction while level.
+    up: Vector,
+    /// Forward direction.
+    forward: Vector,
+    /// Direction of level × nose-up accelerometer measurements.
+    nose_up: Vector,
+    /// Direction of level × rolled-right accelerometer measurements.
+    rolled_right: Vector,
+}
+
+impl BodyAxes {
+    fn new(convention: Convention) -> Self {
+        let x = Vector::new(1.0, 0.0, 0.0);
+        let y = Vector::new(0.0, 1.0, 0.0);
+        let z = Vector::new(0.0, 0.0, 1.0);
+        match convention {
+            Convention::NorthWestUp => Self {
+                up: z,
+                forward: x,
+                nose_up: y,
+                rolled_right: -x,
+            },
+            Convention::EastNorthUp => Self {
+                up: z,
+                forward: y,
+                nose_up: -x,
+                rolled_right: -y,
+            },
+            Convention::NorthWestDown => Self {
+                up: -z,
+                forward: x,
+                nose_up: -y,
+                rolled_right: -x,
+            },
+        }
+    }
+}
+
+/// Fixed rotation from the sensor frame to the vehicle body frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SensorAlignment {
+    /// Quaternion that rotates vectors from the sensor frame to the body
+    /// frame.
+    pub quaternion: Quaternion,
+}
+
+impl SensorAlignment {
+    /// Returns the rotation matrix from the sensor frame to the body frame.
+    ///
+    /// This can be used as the misalignment matrix of
+    /// [`calibration_inertial`](crate::calibration_inertial) for a sensor
+    /// without other misalignment.
+    pub fn matrix(&self) -> Matrix {
+        self.quaternion.to_matrix()
+    }
+
+    /// Rotates a vector from the sensor frame to the body frame.
+    pub fn apply(&self, vector: Vector) -> Vector {
+        self.quaternion.rotate(vector)
+    }
+
+    /// Returns the calibration with the alignment included in the
+    /// misalignment matrix so that calibrated measurements are in the body
+    /// frame.
+    pub fn apply_to_calibration(&self, calibration: InertialCalibration) -> InertialCalibration {
+        InertialCalibration {
+            misalignment: self.matrix() * calibration.misalignment,
+            ..calibration
+        }
+    }
+
+    /// Converts the orientation of the sensor relative to the Earth, as
+    /// provided by [`FusionAhrs::get_quaternion`](crate::FusionAhrs::get_quaternion),
+    /// to the orientation of the body relative to the Earth.
+    pub fn body_quaternion(&self, sensor: Quaternion) -> Quaternion {
+        (sensor * self.quaternion.conjugate()).normalise()
+    }
+}
+
+/// Sensor alignment estimation error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum AlignmentError {
+    /// No level samples.
+    MissingLevel,
+    /// No nose-up, rolled-right, or forward acceleration samples.
+    MissingSecondAttitude,
+    /// The second attitude is too close to level.
+    InsufficientRotation,
+    /// The forward acceleration is too small.
+    InsufficientAcceleration,
+}
+
+impl fmt::Display for AlignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLevel => write!(f, "missing level samples"),
+            Self::MissingSecondAttitude => write!(f, "missing second attitude samples"),
+            Self::InsufficientRotation => write!(f, "second attitude too close to level"),
+            Self::InsufficientAcceleration => write!(f, "forward acceleration too small"),
+        }
+    }
+}
+
+/// Running mean of vectors.
+#[derive(Debug, Clone, Copy, Default)]
+struct Mean {
+    sum: [f64; 3],
+    count: u32,
+}
+
+impl Mean {
+    fn add(&mut self, vector: Vector) {
+        self.sum[0] += vector.x as f64;
+        self.sum[1] += vector.y as f64;
+        self.sum[2] += vector.z as f64;
+        self.count += 1;
+    }
+
+    fn mean(&self) -> Option<Vector> {
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f64;
+        Some(Vector::new(
+            (self.sum[0] / count) as f32,
+            (self.sum[1] / count) as f32,
+            (self.sum[2] / count) as f32,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mounting() -> Quaternion {
+        Quaternion::from_axis_angle(Vector::new(0.3, -0.5, 1.0), 12.0)
+    }
+
+    /// Returns the accelerometer measurement in the sensor frame.
+    fn measure(body: Vector) -> Vector {
+        mounting().conjugate().rotate(body)
+    }
+
+    #[test]
+    fn nose_up_alignment_is_estimated() {
+        let pitch = 20_f32.to_radians();
+        let mut estimator = AlignmentEstimator::new(Convention::NorthWestUp);
+        estimator.add_level_sample(measure(Vector::new(0.0, 0.0, 1.0)));
+        estimator.add_nose_up_sample(measure(Vector::new(
+            libm::sinf(pitch),
+            0.0,
+            libm::cosf(pitch),
+        )));
+
+        // Act
+        let alignment = estimator.estimate().unwrap();
+
+        assert!(alignment.quaternion.angle_to(mounting()) < 0.01);
+    }
+
+    #[test]
+    fn forward_acceleration_alignment_is_estimated() {
+        let mut estimator = AlignmentEstimator::new(Convention::NorthWestDown);
+        estimator.add_level_sample(measure(Vector::new(0.0, 0.0, -1.0)));
+        estimator.add_forward_acceleration_sample(measure(Vector::new(0.3, 0.0, -1.0)));
+
+        // Act
+        let alignment = estimator.estimate().unwrap();
+
+        assert!(alignment.quaternion.angle_to(mounting()) < 0.01);
+    }
+
+    #[test]
+    fn rolled_right_alignment_gives_level_body() {
+        let roll = 15_f32.to_radians();
+        let mut estimator = AlignmentEstimator::new(Convention::EastNorthUp);
+        estimator.add_level_sample(measure(Vector::new(0.0, 0.0, 1.0)));
+        estimator.add_rolled_right_sample(measure(Vector::new(
+            -libm::sinf(roll),
+            0.0,
+            libm::cosf(roll),
+        )));
+        let alignment = estimator.estimate().unwrap();
+
+        // Act
+        let body = alignment.body_quaternion(mounting());
+
+        assert!(body.angle_to(Quaternion::IDENTITY) < 0.01);
+    }
+
+    #[test]
+    fn level_only_is_rejected() {
+        let mut estimator = AlignmentEstimator::new(Convention::NorthWestUp);
+        estimator.add_level_sample(Vector::new(0.0, 0.0, 1.0));
+
+        // Act
+        let result = estimator.estimate();
+
+        assert_eq!(result, Err(AlignmentError::MissingSecondAttitude));
+    }
+}
@@ -0,0 +1,258 @@
+use std::vec::Vec;
+
+use crate::Vector;
+
+/// Ratio of the Allan deviation minimum to the bias instability for flicker
+/// noise: sqrt(2 ln 2 / π).
+const BIAS_INSTABILITY_FACTOR: f64 = 0.664;
+
+/// Number of cluster sizes per decade of averaging time.
+const POINTS_PER_DECADE: f64 = 10.0;
+
+/// Point on an Allan deviation curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllanPoint {
+    /// Averaging time in seconds.
+    pub tau: f32,
+    /// Allan deviation in the units of the samples.
+    pub deviation: f32,
+    /// Number of overlapping clusters used.
+    pub clusters: usize,
+}
+
+/// Returns the overlapping Allan deviation of a single axis.
+///
+/// Cluster sizes are logarithmically spaced from one sample up to half the
+/// recording length.
+///
+/// Arguments:
+/// - `samples`: Samples recorded while stationary.
+/// - `sample_rate`: Sample rate in Hz.
+pub fn allan_deviation(samples: &[f32], sample_rate: f32) -> Vec<AllanPoint> {
+    let period = 1.0 / sample_rate as f64;
+
+    // Integrate the samples so that cluster averages are differences
+    let mut integral = Vec::with_capacity(samples.len() + 1);
+    let mut sum = 0.0;
+    integral.push(sum);
+    for &sample in samples {
+        sum += sample as f64 * period;
+        integral.push(sum);
+    }
+
+    let length = samples.len();
+    let mut points = Vec::new();
+    let mut previous = 0;
+    for index in 0.. {
+        let size = 10_f64.powf(index as f64 / POINTS_PER_DECADE).round() as usize;
+        if size == previous {
+            continue;
+        }
+        previous = size;
+        if 2 * size >= length {
+            break;
+        }
+        let tau = size as f64 * period;
+        let clusters = length + 1 - 2 * size;
+        let sum: f64 = (0..clusters)
+            .map(|k| {
+                let difference = integral[k + 2 * size] - 2.0 * integral[k + size] + integral[k];
+                difference * difference
+            })
+            .sum();
+        let variance = sum / (2.0 * tau * tau * clusters as f64);
+        points.push(AllanPoint {
+            tau: tau as f32,
+            deviation: variance.sqrt() as f32,
+            clusters,
+        });
+    }
+    points
+}
+
+/// Noise coefficients extracted from an Allan deviation curve.
+///
+/// For a gyroscope in degrees per second, the white noise coefficient is the
+/// angle random walk in degrees per root second (multiply by 60 for degrees
+/// per root hour). For an accelerometer in g, it is the velocity random walk
+/// in g per root second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseCoefficients {
+    /// White noise coefficient N: the Allan deviation at 1 s of the line with
+    /// slope -1/2. Units per root second.
+    pub white_noise: f32,
+    /// Bias instability B: the minimum Allan deviation divided by 0.664.
+    pub bias_instability: f32,
+    /// Rate random walk coefficient K: the Allan deviation at 3 s of the line
+    /// with slope +1/2. Units per second per root second.
+    pub random_walk: f32,
+}
+
+impl NoiseCoefficients {
+    /// Extracts the noise coefficients from an Allan deviation curve.
+    ///
+    /// Only averaging times up to a tenth of the recording length are used.
+    /// Returns `None` if there are too few points.
+    pub fn from_curve(curve: &[AllanPoint]) -> Option<Self> {
+        let first = curve.first()?;
+        let maximum_tau = first.tau * (first.clusters + 1) as f32 / 10.0;
+        let points: Vec<_> = curve
+            .iter()
+            .filter(|point| point.tau <= maximum_tau && point.deviation > 0.0)
+            .map(|point| ((point.tau as f64).ln(), (point.deviation as f64).ln()))
+            .collect();
+        if points.len() < 3 {
+            return None;
+        }
+
+        let slopes: Vec<_> = (0..points.len())
+            .map(|index| slope(&points, index))
+            .collect();
+        // Returns the curve point whose local slope is closest to `target`
+        let closest = |target: f64| {
+            let index = (0..points.len())
+                .min_by(|&a, &b| {
+                    (slopes[a] - target)
+                        .abs()
+                        .total_cmp(&(slopes[b] - target).abs())
+                })
+                .unwrap_or(0);
+            points[index]
+        };
+
+        let (log_tau, log_deviation) = closest(-0.5);
+        let white_noise = (log_deviation + 0.5 * log_tau).exp();
+        let (log_tau, log_deviation) = closest(0.5);
+        let random_walk = (log_deviation - 0.5 * log_tau + 0.5 * 3_f64.ln()).exp();
+        let minimum = points
+            .iter()
+            .map(|&(_, log_deviation)| log_deviation)
+            .fold(f64::INFINITY, f64::min)
+            .exp();
+
+        Some(Self {
+            white_noise: white_noise as f32,
+            bias_instability: (minimum / BIAS_INSTABILITY_FACTOR) as f32,
+            random_walk: random_walk as f32,
+        })
+    }
+}
+
+/// Returns the least squares slope of the log-log curve over the
+/// neighbouring points.
+fn slope(points: &[(f64, f64)], index: usize) -> f64 {
+    let window = &points[index.saturating_sub(3)..(index + 4).min(points.len())];
+    let count = window.len() as f64;
+    let mean_x = window.iter().map(|p| p.0).sum::<f64>() / count;
+    let mean_y = window.iter().map(|p| p.1).sum::<f64>() / count;
+    let covariance: f64 = window.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = window.iter().map(|p| (p.0 - mean_x) * (p.0 - mean_x)).sum();
+    covariance / variance
+}
+
+/// Allan deviation curves of the three axes of a gyroscope or accelerometer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllanDeviation {
+    /// X axis curve.
+    pub x: Vec<AllanPoint>,
+    /// Y axis curve.
+    pub y: Vec<AllanPoint>,
+    /// Z axis curve.
+    pub z: Vec<AllanPoint>,
+}
+
+impl AllanDeviation {
+    /// Computes the overlapping Allan deviation of each axis.
+    ///
+    /// Arguments:
+    /// - `samples`: Samples recorded while stationary.
+    /// - `sample_rate`: Sample rate in Hz.
+    pub fn new(samples: &[Vector], sample_rate: f32) -> Self {
+        let axis = |select: fn(&Vector) -> f32| {
+            let values: Vec<f32> = samples.iter().map(select).collect();
+            allan_deviation(&values, sample_rate)
+        };
+        Self {
+            x: axis(|vector| vector.x),
+            y: axis(|vector| vector.y),
+            z: axis(|vector| vector.z),
+        }
+    }
+
+    /// Extracts the noise coefficients of each axis. Returns `None` if the
+    /// recording is too short.
+    pub fn noise_coefficients(&self) -> Option<[NoiseCoefficients; 3]> {
+        Some([
+            NoiseCoefficients::from_curve(&self.x)?,
+            NoiseCoefficients::from_curve(&self.y)?,
+            NoiseCoefficients::from_curve(&self.z)?,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 100.0;
+
+    /// Deterministic normally distributed numbers.
+    struct Normal(u64);
+
+    impl Normal {
+        fn uniform(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn next(&mut self) -> f64 {
+            let u = self.uniform().max(f64::MIN_POSITIVE);
+            let v = self.uniform();
+            (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+        }
+    }
+
+    #[test]
+    fn white_noise_coefficient_is_extracted() {
+        let deviation = 0.1;
+        let mut normal = Normal(0x2545f4914f6cdd1d);
+        let samples: Vec<f32> = (0..200_000)
+            .map(|_| (normal.next() * deviation) as f32)
+            .collect();
+
+        // Act
+        let curve = allan_deviation(&samples, SAMPLE_RATE);
+
+        let coefficients = NoiseCoefficients::from_curve(&curve).unwrap();
+        let expected = deviation as f32 / SAMPLE_RATE.sqrt();
+        assert!((coefficients.white_noise / expected - 1.0).abs() < 0.05);
+        let first = curve[0];
+        assert!((first.deviation / deviation as f32 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn random_walk_coefficient_is_extracted() {
+        let expected = 0.01;
+        let step = expected / SAMPLE_RATE.sqrt() as f64;
+        let mut normal = Normal(0x9e3779b97f4a7c15);
+        let mut value = 0.0;
+        let samples: Vec<Vector> = (0..200_000)
+            .map(|_| {
+                value += normal.next() * step;
+                Vector::new(value as f32, 0.0, 0.0)
+            })
+            .collect();
+
+        // Act
+        let curves = AllanDeviation::new(&samples, SAMPLE_RATE);
+
+        let coefficients = NoiseCoefficients::from_curve(&curves.x).unwrap();
+        assert!((coefficients.random_walk / expected as f32 - 1.0).abs() < 0.25);
+        assert!(curves.y.iter().all(|point| point.deviation == 0.0));
+    }
+}
@@ -0,0 +1,87 @@
+use fusion_imu_sys as sys;
+
+use crate::Vector;
+
+/// Axes alignment describing the sensor axes relative to the body axes. For
+/// example, if the body X axis is aligned with the sensor Y axis and the body
+/// Y axis is aligned with sensor X axis but pointing the opposite direction
+/// then alignment is +Y-X+Z.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[repr(C)]
+pub enum AxesAlignment {
+    /// +X+Y+Z
+    #[default]
+    PxPyPz,
+    /// +X-Z+Y
+    PxNzPy,
+    /// +X-Y-Z
+    PxNyNz,
+    /// +X+Z-Y
+    PxPzNy,
+    /// -X+Y-Z
+    NxPyNz,
+    /// -X+Z+Y
+    NxPzPy,
+    /// -X-Y+Z
+    NxNyPz,
+    /// -X-Z-Y
+    NxNzNy,
+    /// +Y-X+Z
+    PyNxPz,
+    /// +Y-Z-X
+    PyNzNx,
+    /// +Y+X-Z
+    PyPxNz,
+    /// +Y+Z+X
+    PyPzPx,
+    /// -Y+X+Z
+    NyPxPz,
+    /// -Y-Z+X
+    NyNzPx,
+    /// -Y-X-Z
+    NyNxNz,
+    /// -Y+Z-X
+    NyPzNx,
+    /// +Z+Y-X
+    PzPyNx,
+    /// +Z+X+Y
+    PzPxPy,
+    /// +Z-Y+X
+    PzNyPx,
+    /// +Z-X-Y
+    PzNxNy,
+    /// -Z+Y+X
+    NzPyPx,
+    /// -Z-X+Y
+    NzNxPy,
+    /// -Z-Y-X
+    NzNyNx,
+    /// -Z+X-Y
+    NzPxNy,
+}
+
+/// Swaps sensor axes for alignment with the body axes.
+///
+/// Arguments:
+/// - `sensor`: Sensor axes.
+/// - `alignment`: Axes alignment.
+pub fn axes_swap(sensor: Vector, alignment: AxesAlignment) -> Vector {
+    unsafe { sys::FusionAxesSwap(sensor.into(), alignment as sys::FusionAxesAlignment).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_are_swapped() {
+        let sensor = Vector::new(1.0, 2.0, 3.0);
+
+        // Act
+        let body = axes_swap(sensor, AxesAlignment::PyNxPz);
+
+        assert_eq!(body, Vector::new(2.0, -1.0, 3.0));
+    }
+}
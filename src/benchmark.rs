@@ -0,0 +1,388 @@
+use crate::{
+    wrap_180, Ahrs, FusionAhrs, FusionOffset, Quaternion, Settings, SimulatedSample, Vector,
+};
+
+/// Sensor measurements with the true orientation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct TruthSample {
+    /// Time in seconds.
+    pub time: f32,
+    /// Gyroscope measurement in degrees per second.
+    pub gyroscope: Vector,
+    /// Accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Magnetometer measurement in arbitrary units, if available.
+    pub magnetometer: Option<Vector>,
+    /// True orientation of the sensor relative to the Earth.
+    pub quaternion: Quaternion,
+}
+
+impl From<SimulatedSample> for TruthSample {
+    fn from(sample: SimulatedSample) -> Self {
+        Self {
+            time: sample.time,
+            gyroscope: sample.gyroscope,
+            accelerometer: sample.accelerometer,
+            magnetometer: Some(sample.magnetometer),
+            quaternion: sample.quaternion,
+        }
+    }
+}
+
+/// Benchmark configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BenchmarkConfig {
+    /// AHRS algorithm settings under test.
+    pub settings: Settings,
+    /// Sample rate in Hz, used for the first sample and the gyroscope offset
+    /// correction.
+    pub sample_rate: u32,
+    /// Whether the gyroscope offset is corrected at runtime.
+    pub offset_correction: bool,
+    /// Attitude error in degrees below which the algorithm is considered
+    /// converged.
+    pub convergence_threshold: f32,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            sample_rate: 100,
+            offset_correction: true,
+            convergence_threshold: 2.0,
+        }
+    }
+}
+
+/// RMS and maximum of an error in degrees.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ErrorStatistics {
+    /// Root mean square error in degrees.
+    pub rms: f32,
+    /// Maximum absolute error in degrees.
+    pub maximum: f32,
+}
+
+/// Convergence times after recovery events.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ConvergenceStatistics {
+    /// Number of recovery events.
+    pub events: u32,
+    /// Number of events after which the attitude error fell below the
+    /// threshold.
+    pub converged: u32,
+    /// Mean convergence time in seconds of the converged events.
+    pub mean: f32,
+    /// Maximum convergence time in seconds of the converged events.
+    pub maximum: f32,
+}
+
+/// Accuracy of an AHRS algorithm configuration over a dataset.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BenchmarkReport {
+    /// Number of samples.
+    pub samples: u32,
+    /// Geodesic attitude error, the angle of the rotation between the true
+    /// and estimated orientations.
+    pub attitude: ErrorStatistics,
+    /// Roll error.
+    pub roll: ErrorStatistics,
+    /// Pitch error.
+    pub pitch: ErrorStatistics,
+    /// Yaw error.
+    pub yaw: ErrorStatistics,
+    /// Heading error, the rotation of the attitude error about the Earth's
+    /// vertical axis. Unlike the yaw error, this is not coupled to tilt.
+    pub heading: ErrorStatistics,
+    /// Time in seconds from the start until the attitude error first fell
+    /// below the threshold, if it did.
+    pub start_convergence: Option<f32>,
+    /// Convergence after the angular rate, acceleration, or magnetic recovery
+    /// flags were set.
+    pub recovery_convergence: ConvergenceStatistics,
+    /// Fraction of samples with the initialising flag set.
+    pub initialising: f32,
+    /// Fraction of samples with the angular rate recovery flag set.
+    pub angular_rate_recovery: f32,
+    /// Fraction of samples with the acceleration recovery flag set.
+    pub acceleration_recovery: f32,
+    /// Fraction of samples with the magnetic recovery flag set.
+    pub magnetic_recovery: f32,
+}
+
+/// Accuracy benchmark of an AHRS algorithm configuration against ground
+/// truth.
+pub struct Benchmark<A = FusionAhrs> {
+    config: BenchmarkConfig,
+    ahrs: A,
+    offset: FusionOffset,
+    start_time: Option<f32>,
+    previous_time: f32,
+    attitude: Accumulator,
+    roll: Accumulator,
+    pitch: Accumulator,
+    yaw: Accumulator,
+    heading: Accumulator,
+    start_convergence: Option<f32>,
+    recovering: bool,
+    recovery_start: Option<f32>,
+    recovery_events: u32,
+    recovery_converged: u32,
+    recovery_sum: f32,
+    recovery_maximum: f32,
+    flag_counts: [u32; 4],
+}
+
+impl Benchmark {
+    /// Create a new `Benchmark` instance.
+    pub fn new(config: BenchmarkConfig) -> Self {
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(config.settings);
+        Benchmark::with_ahrs(config, ahrs)
+    }
+
+    /// Runs the benchmark over a dataset and returns the report.
+    pub fn run(
+        config: BenchmarkConfig,
+        samples: impl IntoIterator<Item = impl Into<TruthSample>>,
+    ) -> BenchmarkReport {
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(config.settings);
+        Benchmark::run_with_ahrs(config, ahrs, samples)
+    }
+}
+
+impl<A: Ahrs> Benchmark<A> {
+    /// Create a new `Benchmark` instance with another AHRS algorithm. The
+    /// AHRS algorithm settings of the configuration are not used.
+    pub fn with_ahrs(config: BenchmarkConfig, ahrs: A) -> Self {
+        Self {
+            config,
+            ahrs,
+            offset: FusionOffset::new(config.sample_rate),
+            start_time: None,
+            previous_time: 0.0,
+            attitude: Accumulator::default(),
+            roll: Accumulator::default(),
+            pitch: Accumulator::default(),
+            yaw: Accumulator::default(),
+            heading: Accumulator::default(),
+            start_convergence: None,
+            recovering: false,
+            recovery_start: None,
+            recovery_events: 0,
+            recovery_converged: 0,
+            recovery_sum: 0.0,
+            recovery_maximum: 0.0,
+            flag_counts: [0; 4],
+        }
+    }
+
+    /// Runs the benchmark over a dataset with another AHRS algorithm and
+    /// returns the report.
+    pub fn run_with_ahrs(
+        config: BenchmarkConfig,
+        ahrs: A,
+        samples: impl IntoIterator<Item = impl Into<TruthSample>>,
+    ) -> BenchmarkReport {
+        let mut benchmark = Self::with_ahrs(config, ahrs);
+        for sample in samples {
+            benchmark.update(&sample.into());
+        }
+        benchmark.report()
+    }
+
+    /// Returns the AHRS algorithm.
+    pub fn ahrs(&self) -> &A {
+        &self.ahrs
+    }
+
+    /// Updates the AHRS algorithm with a sample and accumulates its error.
+    pub fn update(&mut self, sample: &TruthSample) {
+        let sample_period = 1.0 / self.config.sample_rate as f32;
+        let delta_time = match self.start_time {
+            Some(_) => sample.time - self.previous_time,
+            None => sample_period,
+        };
+        let start_time = *self.start_time.get_or_insert(sample.time - sample_period);
+        self.previous_time = sample.time;
+        let elapsed = sample.time - start_time;
+
+        let gyroscope = if self.config.offset_correction {
+            self.offset.update(sample.gyroscope)
+        } else {
+            sample.gyroscope
+        };
+        match sample.magnetometer {
+            Some(magnetometer) => {
+                self.ahrs
+                    .update(gyroscope, sample.accelerometer, magnetometer, delta_time)
+            }
+            None => self
+                .ahrs
+                .update_no_magnetometer(gyroscope, sample.accelerometer, delta_time),
+        }
+
+        // Errors
+        let estimate = self.ahrs.quaternion();
+        let attitude_error = estimate.angle_to(sample.quaternion);
+        self.attitude.add(attitude_error);
+        let (estimated, truth) = (estimate.to_euler(), sample.quaternion.to_euler());
+        self.roll.add(wrap_180(estimated.roll - truth.roll));
+        self.pitch.add(wrap_180(estimated.pitch - truth.pitch));
+        self.yaw.add(wrap_180(estimated.yaw - truth.yaw));
+        let error = estimate * sample.quaternion.conjugate();
+        let heading = 2.0 * libm::atan2f(error.z, error.w).to_degrees();
+        self.heading.add(wrap_180(heading));
+
+        // Convergence
+        let converged = attitude_error < self.config.convergence_threshold;
+        if converged && self.start_convergence.is_none() {
+            self.start_convergence = Some(elapsed);
+        }
+        let flags = self.ahrs.flags();
+        let recovering = flags.angular_rate_recovery()
+            || flags.acceleration_recovery()
+            || flags.magnetic_recovery();
+        if recovering && !self.recovering && self.recovery_start.is_none() {
+            self.recovery_events += 1;
+            self.recovery_start = Some(elapsed);
+        }
+        self.recovering = recovering;
+        if let Some(recovery_start) = self.recovery_start.filter(|_| converged) {
+            let time = elapsed - recovery_start;
+            self.recovery_converged += 1;
+            self.recovery_sum += time;
+            self.recovery_maximum = self.recovery_maximum.max(time);
+            self.recovery_start = None;
+        }
+
+        // Flags
+        for (count, flag) in self.flag_counts.iter_mut().zip([
+            flags.initialising(),
+            flags.angular_rate_recovery(),
+            flags.acceleration_recovery(),
+            flags.magnetic_recovery(),
+        ]) {
+            *count += flag as u32;
+        }
+    }
+
+    /// Returns the report of the samples so far.
+    pub fn report(&self) -> BenchmarkReport {
+        let samples = self.attitude.count;
+        let fraction = |count: u32| {
+            if samples == 0 {
+                0.0
+            } else {
+                count as f32 / samples as f32
+            }
+        };
+        BenchmarkReport {
+            samples,
+            attitude: self.attitude.statistics(),
+            roll: self.roll.statistics(),
+            pitch: self.pitch.statistics(),
+            yaw: self.yaw.statistics(),
+            heading: self.heading.statistics(),
+            start_convergence: self.start_convergence,
+            recovery_convergence: ConvergenceStatistics {
+                events: self.recovery_events,
+                converged: self.recovery_converged,
+                mean: if self.recovery_converged == 0 {
+                    0.0
+                } else {
+                    self.recovery_sum / self.recovery_converged as f32
+                },
+                maximum: self.recovery_maximum,
+            },
+            initialising: fraction(self.flag_counts[0]),
+            angular_rate_recovery: fraction(self.flag_counts[1]),
+            acceleration_recovery: fraction(self.flag_counts[2]),
+            magnetic_recovery: fraction(self.flag_counts[3]),
+        }
+    }
+}
+
+/// Accumulator of squared and maximum errors.
+#[derive(Default)]
+struct Accumulator {
+    count: u32,
+    sum_squares: f64,
+    maximum: f32,
+}
+
+impl Accumulator {
+    fn add(&mut self, error: f32) {
+        self.count += 1;
+        self.sum_squares += error as f64 * error as f64;
+        self.maximum = self.maximum.max(libm::fabsf(error));
+    }
+
+    fn statistics(&self) -> ErrorStatistics {
+        if self.count == 0 {
+            return ErrorStatistics::default();
+        }
+        ErrorStatistics {
+            rms: libm::sqrt(self.sum_squares / self.count as f64) as f32,
+            maximum: self.maximum,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Segment, SimulationConfig, Simulator};
+
+    #[test]
+    fn ideal_sensors_converge() {
+        let trajectory = [
+            Segment::stationary(5.0),
+            Segment::rotation(2.0, Vector::new(0.0, 0.0, 45.0)),
+            Segment::stationary(3.0),
+        ];
+        let simulator = Simulator::new(SimulationConfig::default(), &trajectory);
+
+        // Act
+        let report = Benchmark::run(BenchmarkConfig::default(), simulator);
+
+        assert_eq!(report.samples, 1000);
+        assert!((report.start_convergence.unwrap() - 0.01).abs() < 1e-6);
+        assert!(report.attitude.maximum < 1.0);
+        assert!(report.heading.rms < 0.5);
+        assert!((report.initialising - 0.3).abs() < 0.01);
+        assert_eq!(report.recovery_convergence.events, 0);
+    }
+
+    #[test]
+    fn initial_tilt_error_is_measured() {
+        let config = SimulationConfig {
+            initial_quaternion: Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), 30.0),
+            ..Default::default()
+        };
+        let trajectory = [Segment::stationary(10.0)];
+        let simulator = Simulator::new(config, &trajectory);
+
+        // Act
+        let report = Benchmark::run(BenchmarkConfig::default(), simulator);
+
+        assert!(report.attitude.maximum > 25.0 && report.attitude.maximum < 30.0);
+        assert!(report.roll.maximum > 25.0 && report.roll.maximum < 30.0);
+        assert!(report.pitch.maximum < 1.0);
+        assert!(report.heading.rms < 5.0);
+        let convergence = report.start_convergence.unwrap();
+        assert!(convergence > 0.05 && convergence < 3.0);
+        assert!(report.attitude.rms < 5.0);
+    }
+}
@@ -38,3 +38,71 @@ pub fn calibration_magnetic(uncalibrated: Vector, soft_iron: Matrix, hard_iron:
             .into()
     }
 }
+
+/// Gyroscope or accelerometer calibration parameters.
+///
+/// The default value is the identity calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct InertialCalibration {
+    /// Misalignment matrix.
+    pub misalignment: Matrix,
+    /// Sensitivity vector.
+    pub sensitivity: Vector,
+    /// Offset vector.
+    pub offset: Vector,
+}
+
+impl Default for InertialCalibration {
+    fn default() -> Self {
+        Self {
+            misalignment: Matrix::IDENTITY,
+            sensitivity: Vector::ONES,
+            offset: Vector::ZERO,
+        }
+    }
+}
+
+impl InertialCalibration {
+    /// Applies the calibration to an uncalibrated measurement using
+    /// [`calibration_inertial`].
+    pub fn apply(&self, uncalibrated: Vector) -> Vector {
+        calibration_inertial(
+            uncalibrated,
+            self.misalignment,
+            self.sensitivity,
+            self.offset,
+        )
+    }
+}
+
+/// Magnetometer calibration parameters.
+///
+/// The default value is the identity calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MagneticCalibration {
+    /// Soft-iron matrix.
+    pub soft_iron: Matrix,
+    /// Hard-iron offset vector.
+    pub hard_iron: Vector,
+}
+
+impl Default for MagneticCalibration {
+    fn default() -> Self {
+        Self {
+            soft_iron: Matrix::IDENTITY,
+            hard_iron: Vector::ZERO,
+        }
+    }
+}
+
+impl MagneticCalibration {
+    /// Applies the calibration to an uncalibrated measurement using
+    /// [`calibration_magnetic`].
+    pub fn apply(&self, uncalibrated: Vector) -> Vector {
+        calibration_magnetic(uncalibrated, self.soft_iron, self.hard_iron)
+    }
+}
@@ -0,0 +1,420 @@
+use crate::math::symmetric_eigen;
+use crate::{InertialCalibration, MagneticCalibration, Matrix, Vector};
+
+/// Limits used by [`CalibrationReport`] to flag bad calibrations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct CalibrationLimits {
+    /// Maximum condition number of the misalignment and soft-iron matrices.
+    pub maximum_condition_number: f32,
+    /// Maximum gyroscope offset magnitude in degrees per second.
+    pub maximum_gyroscope_offset: f32,
+    /// Maximum accelerometer offset magnitude in g.
+    pub maximum_accelerometer_offset: f32,
+    /// Maximum hard-iron offset magnitude relative to the field strength.
+    pub maximum_hard_iron: f32,
+    /// Maximum RMS gravity magnitude error of static accelerometer
+    /// measurements in g.
+    pub maximum_gravity_error: f32,
+    /// Maximum standard deviation of the calibrated field magnitude relative
+    /// to the field strength.
+    pub maximum_field_spread: f32,
+}
+
+impl Default for CalibrationLimits {
+    fn default() -> Self {
+        Self {
+            maximum_condition_number: 2.0,
+            maximum_gyroscope_offset: 10.0,
+            maximum_accelerometer_offset: 0.25,
+            maximum_hard_iron: 5.0,
+            maximum_gravity_error: 0.02,
+            maximum_field_spread: 0.05,
+        }
+    }
+}
+
+/// Problems found in a calibration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct CalibrationIssues {
+    /// The matrix is singular or not finite.
+    pub singular: bool,
+    /// The matrix has a negative determinant and mirrors the axes.
+    pub reflection: bool,
+    /// The matrix condition number exceeds the limit.
+    pub ill_conditioned: bool,
+    /// The offset exceeds the limit.
+    pub excessive_offset: bool,
+    /// The residual error of the calibrated samples exceeds the limit.
+    pub excessive_error: bool,
+}
+
+impl CalibrationIssues {
+    /// Returns true if no problems were found.
+    pub fn is_ok(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Properties of a calibration matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MatrixReport {
+    /// Determinant.
+    pub determinant: f32,
+    /// Ratio of the largest to the smallest singular value.
+    pub condition_number: f32,
+}
+
+impl MatrixReport {
+    /// Create a new `MatrixReport` for a matrix.
+    pub fn new(matrix: Matrix) -> Self {
+        let (values, _) = symmetric_eigen(matrix.transpose() * matrix);
+        let largest = values.x.max(values.y).max(values.z);
+        let smallest = values.x.min(values.y).min(values.z);
+        let condition_number = if smallest > 0.0 {
+            libm::sqrtf(largest / smallest)
+        } else {
+            f32::INFINITY
+        };
+        Self {
+            determinant: matrix.determinant(),
+            condition_number,
+        }
+    }
+
+    fn issues(&self, limits: &CalibrationLimits) -> CalibrationIssues {
+        let singular = !self.determinant.is_finite()
+            || libm::fabsf(self.determinant) < 1e-6
+            || !self.condition_number.is_finite();
+        CalibrationIssues {
+            singular,
+            reflection: self.determinant < 0.0,
+            ill_conditioned: !singular && self.condition_number > limits.maximum_condition_number,
+            ..Default::default()
+        }
+    }
+}
+
+/// Gyroscope calibration report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct GyroscopeReport {
+    /// Misalignment matrix scaled by the sensitivity.
+    pub matrix: MatrixReport,
+    /// Offset magnitude in degrees per second.
+    pub offset: f32,
+    /// Problems found.
+    pub issues: CalibrationIssues,
+}
+
+/// Accelerometer calibration report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AccelerometerReport {
+    /// Misalignment matrix scaled by the sensitivity.
+    pub matrix: MatrixReport,
+    /// Offset magnitude in g.
+    pub offset: f32,
+    /// RMS gravity magnitude error of the calibrated static samples in g.
+    pub gravity_error_rms: f32,
+    /// Maximum absolute gravity magnitude error of the calibrated static
+    /// samples in g.
+    pub gravity_error_max: f32,
+    /// Number of static samples.
+    pub samples: u32,
+    /// Problems found.
+    pub issues: CalibrationIssues,
+}
+
+/// Magnetometer calibration report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MagnetometerReport {
+    /// Soft-iron matrix.
+    pub soft_iron: MatrixReport,
+    /// Hard-iron offset magnitude relative to the field strength.
+    pub hard_iron: f32,
+    /// Mean calibrated field magnitude.
+    pub field_strength: f32,
+    /// Standard deviation of the calibrated field magnitude relative to the
+    /// field strength.
+    pub field_spread: f32,
+    /// Minimum calibrated field magnitude.
+    pub field_minimum: f32,
+    /// Maximum calibrated field magnitude.
+    pub field_maximum: f32,
+    /// Number of samples.
+    pub samples: u32,
+    /// Problems found.
+    pub issues: CalibrationIssues,
+}
+
+/// Calibration quality report and sanity checks.
+///
+/// Each sensor is checked with its calibration and a set of uncalibrated
+/// samples: static poses for the accelerometer, and samples covering as many
+/// orientations as possible for the magnetometer.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct CalibrationReport {
+    /// Limits used to flag problems.
+    pub limits: CalibrationLimits,
+    /// Gyroscope report.
+    pub gyroscope: Option<GyroscopeReport>,
+    /// Accelerometer report.
+    pub accelerometer: Option<AccelerometerReport>,
+    /// Magnetometer report.
+    pub magnetometer: Option<MagnetometerReport>,
+}
+
+impl CalibrationReport {
+    /// Create a new empty `CalibrationReport` instance.
+    pub fn new(limits: CalibrationLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Checks a gyroscope calibration.
+    pub fn with_gyroscope(mut self, calibration: &InertialCalibration) -> Self {
+        let matrix = MatrixReport::new(inertial_matrix(calibration));
+        let offset = calibration.offset.magnitude();
+        let issues = CalibrationIssues {
+            excessive_offset: exceeds(offset, self.limits.maximum_gyroscope_offset),
+            ..matrix.issues(&self.limits)
+        };
+        self.gyroscope = Some(GyroscopeReport {
+            matrix,
+            offset,
+            issues,
+        });
+        self
+    }
+
+    /// Checks an accelerometer calibration against uncalibrated static
+    /// samples.
+    pub fn with_accelerometer(
+        mut self,
+        calibration: &InertialCalibration,
+        static_samples: impl IntoIterator<Item = Vector>,
+    ) -> Self {
+        let mut statistics = Statistics::default();
+        for sample in static_samples {
+            statistics.add(calibration.apply(sample).magnitude() - 1.0);
+        }
+        let matrix = MatrixReport::new(inertial_matrix(calibration));
+        let offset = calibration.offset.magnitude();
+        let gravity_error_rms = statistics.rms();
+        let issues = CalibrationIssues {
+            excessive_offset: exceeds(offset, self.limits.maximum_accelerometer_offset),
+            excessive_error: exceeds(gravity_error_rms, self.limits.maximum_gravity_error),
+            ..matrix.issues(&self.limits)
+        };
+        self.accelerometer = Some(AccelerometerReport {
+            matrix,
+            offset,
+            gravity_error_rms,
+            gravity_error_max: statistics.maximum_absolute(),
+            samples: statistics.count,
+            issues,
+        });
+        self
+    }
+
+    /// Checks a magnetometer calibration against uncalibrated samples.
+    pub fn with_magnetometer(
+        mut self,
+        calibration: &MagneticCalibration,
+        samples: impl IntoIterator<Item = Vector>,
+    ) -> Self {
+        let mut statistics = Statistics::default();
+        for sample in samples {
+            statistics.add(calibration.apply(sample).magnitude());
+        }
+        let soft_iron = MatrixReport::new(calibration.soft_iron);
+        let field_strength = statistics.mean();
+        let field_spread = statistics.standard_deviation() / field_strength;
+        let hard_iron = calibration.hard_iron.magnitude() / field_strength;
+        let issues = CalibrationIssues {
+            excessive_offset: exceeds(hard_iron, self.limits.maximum_hard_iron),
+            excessive_error: exceeds(field_spread, self.limits.maximum_field_spread),
+            ..soft_iron.issues(&self.limits)
+        };
+        self.magnetometer = Some(MagnetometerReport {
+            soft_iron,
+            hard_iron,
+            field_strength,
+            field_spread,
+            field_minimum: statistics.minimum,
+            field_maximum: statistics.maximum,
+            samples: statistics.count,
+            issues,
+        });
+        self
+    }
+
+    /// Returns true if none of the checked calibrations have problems.
+    pub fn is_ok(&self) -> bool {
+        self.gyroscope.iter().all(|report| report.issues.is_ok())
+            && self
+                .accelerometer
+                .iter()
+                .all(|report| report.issues.is_ok())
+            && self.magnetometer.iter().all(|report| report.issues.is_ok())
+    }
+}
+
+/// Returns true if the value exceeds the limit or is not a number.
+fn exceeds(value: f32, limit: f32) -> bool {
+    value.is_nan() || value > limit
+}
+
+/// Returns the combined misalignment and sensitivity matrix.
+fn inertial_matrix(calibration: &InertialCalibration) -> Matrix {
+    calibration.misalignment * Matrix::from_diagonal(calibration.sensitivity)
+}
+
+/// Running statistics of a sequence of values.
+#[derive(Debug, Clone, Copy)]
+struct Statistics {
+    count: u32,
+    sum: f64,
+    sum_squares: f64,
+    minimum: f32,
+    maximum: f32,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            sum_squares: 0.0,
+            minimum: f32::NAN,
+            maximum: f32::NAN,
+        }
+    }
+}
+
+impl Statistics {
+    fn add(&mut self, value: f32) {
+        self.count += 1;
+        self.sum += value as f64;
+        self.sum_squares += value as f64 * value as f64;
+        self.minimum = self.minimum.min(value);
+        self.maximum = self.maximum.max(value);
+    }
+
+    fn mean(&self) -> f32 {
+        (self.sum / self.count as f64) as f32
+    }
+
+    fn rms(&self) -> f32 {
+        libm::sqrt(self.sum_squares / self.count as f64) as f32
+    }
+
+    fn standard_deviation(&self) -> f32 {
+        let mean = self.sum / self.count as f64;
+        let variance = self.sum_squares / self.count as f64 - mean * mean;
+        libm::sqrt(variance.max(0.0)) as f32
+    }
+
+    fn maximum_absolute(&self) -> f32 {
+        libm::fabsf(self.minimum).max(libm::fabsf(self.maximum))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn good_accelerometer_calibration_passes() {
+        let calibration = InertialCalibration {
+            offset: Vector::new(0.01, -0.02, 0.03),
+            ..Default::default()
+        };
+        let poses = [
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(0.0, 0.0, -1.0),
+        ];
+
+        // Act
+        let report = CalibrationReport::default()
+            .with_accelerometer(&calibration, poses.map(|pose| pose + calibration.offset));
+
+        let accelerometer = report.accelerometer.unwrap();
+        assert!(report.is_ok());
+        assert!(accelerometer.gravity_error_max < 1e-6);
+        assert_eq!(accelerometer.samples, 6);
+        assert!(libm::fabsf(accelerometer.matrix.condition_number - 1.0) < 1e-6);
+    }
+
+    #[test]
+    fn reflected_soft_iron_is_flagged() {
+        let calibration = MagneticCalibration {
+            soft_iron: Matrix::from_diagonal(Vector::new(1.0, -1.0, 1.0)),
+            hard_iron: Vector::ZERO,
+        };
+        let samples = [Vector::new(50.0, 0.0, 0.0), Vector::new(0.0, 50.0, 0.0)];
+
+        // Act
+        let report = CalibrationReport::default().with_magnetometer(&calibration, samples);
+
+        let issues = report.magnetometer.unwrap().issues;
+        assert!(issues.reflection);
+        assert!(!issues.singular);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn singular_misalignment_is_flagged() {
+        let calibration = InertialCalibration {
+            misalignment: Matrix::from_rows(
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(1.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ),
+            ..Default::default()
+        };
+
+        // Act
+        let report = CalibrationReport::default().with_gyroscope(&calibration);
+
+        let issues = report.gyroscope.unwrap().issues;
+        assert!(issues.singular);
+        assert!(!issues.ill_conditioned);
+    }
+
+    #[test]
+    fn magnetic_spread_and_hard_iron_are_flagged() {
+        let calibration = MagneticCalibration {
+            soft_iron: Matrix::IDENTITY,
+            hard_iron: Vector::new(300.0, 0.0, 0.0),
+        };
+        let samples = [Vector::new(340.0, 0.0, 0.0), Vector::new(300.0, 60.0, 0.0)];
+
+        // Act
+        let report = CalibrationReport::default().with_magnetometer(&calibration, samples);
+
+        let magnetometer = report.magnetometer.unwrap();
+        assert!(magnetometer.issues.excessive_offset);
+        assert!(magnetometer.issues.excessive_error);
+        assert_eq!(magnetometer.field_minimum, 40.0);
+        assert_eq!(magnetometer.field_maximum, 60.0);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::FusionAhrs;
+
+/// Settings of the [`ConfidenceEstimator`] heuristic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ConfidenceSettings {
+    /// Tilt uncertainty in degrees with continuous accelerometer corrections.
+    pub minimum_tilt: f32,
+    /// Heading uncertainty in degrees with continuous magnetometer
+    /// corrections.
+    pub minimum_heading: f32,
+    /// Residual gyroscope bias in degrees per second, which sets the drift of
+    /// the attitude between corrections.
+    pub gyroscope_drift: f32,
+    /// Heading uncertainty in degrees above which the heading is not valid.
+    pub heading_limit: f32,
+}
+
+impl Default for ConfidenceSettings {
+    fn default() -> Self {
+        Self {
+            minimum_tilt: 1.0,
+            minimum_heading: 3.0,
+            gyroscope_drift: 0.1,
+            heading_limit: 20.0,
+        }
+    }
+}
+
+/// Heuristic attitude uncertainty of the AHRS algorithm output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AttitudeConfidence {
+    /// Tilt (roll and pitch) uncertainty in degrees.
+    pub tilt: f32,
+    /// Heading uncertainty in degrees.
+    pub heading: f32,
+    /// Time in seconds since the accelerometer was last used.
+    pub acceleration_age: f32,
+    /// Time in seconds since the magnetometer was last used.
+    pub magnetic_age: f32,
+    /// Whether the heading uncertainty is below the heading limit.
+    pub heading_valid: bool,
+}
+
+impl AttitudeConfidence {
+    /// Confidence while the AHRS algorithm is initialising.
+    pub const UNKNOWN: AttitudeConfidence = AttitudeConfidence {
+        tilt: 90.0,
+        heading: 180.0,
+        acceleration_age: 0.0,
+        magnetic_age: 0.0,
+        heading_valid: false,
+    };
+}
+
+/// Estimator of a heuristic [`AttitudeConfidence`] for [`FusionAhrs`].
+///
+/// Each uncertainty is the minimum uncertainty, plus the gyroscope drift
+/// since the sensor was last used, plus the error of the sensor weighted by
+/// its recovery trigger while it is ignored, or in full during its recovery.
+/// The heading uncertainty also includes the tilt uncertainty. The attitude
+/// is unknown while the algorithm is initialising.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceEstimator {
+    settings: ConfidenceSettings,
+    acceleration_age: f32,
+    magnetic_age: f32,
+    confidence: AttitudeConfidence,
+}
+
+impl ConfidenceEstimator {
+    /// Create a new `ConfidenceEstimator` instance.
+    pub fn new(settings: ConfidenceSettings) -> Self {
+        Self {
+            settings,
+            acceleration_age: 0.0,
+            magnetic_age: 0.0,
+            confidence: AttitudeConfidence::UNKNOWN,
+        }
+    }
+
+    /// Returns the most recent confidence.
+    pub fn confidence(&self) -> AttitudeConfidence {
+        self.confidence
+    }
+
+    /// Updates the confidence after an update of the AHRS algorithm.
+    ///
+    /// Arguments:
+    /// - `ahrs`: AHRS algorithm.
+    /// - `delta_time`: Delta time in seconds of the AHRS algorithm update.
+    pub fn update(&mut self, ahrs: &FusionAhrs, delta_time: f32) -> AttitudeConfidence {
+        let flags = ahrs.get_flags();
+        let states = ahrs.get_internal_states();
+        let settings = &self.settings;
+
+        self.acceleration_age = if states.accelerometer_ignored() {
+            self.acceleration_age + delta_time
+        } else {
+            0.0
+        };
+        self.magnetic_age = if states.magnetometer_ignored() {
+            self.magnetic_age + delta_time
+        } else {
+            0.0
+        };
+        if flags.initialising() || flags.angular_rate_recovery() {
+            self.confidence = AttitudeConfidence {
+                acceleration_age: self.acceleration_age,
+                magnetic_age: self.magnetic_age,
+                ..AttitudeConfidence::UNKNOWN
+            };
+            return self.confidence;
+        }
+
+        // The longer a sensor is ignored, the more likely the attitude is wrong
+        let sensor_error = |error: f32, ignored: bool, trigger: f32, recovery: bool| {
+            if recovery {
+                error
+            } else if ignored {
+                error * trigger.clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        let tilt = settings.minimum_tilt
+            + settings.gyroscope_drift * self.acceleration_age
+            + sensor_error(
+                states.acceleration_error(),
+                states.accelerometer_ignored(),
+                states.acceleration_recovery_trigger(),
+                flags.acceleration_recovery(),
+            );
+        let heading = settings.minimum_heading
+            + tilt
+            + settings.gyroscope_drift * self.magnetic_age
+            + sensor_error(
+                states.magnetic_error(),
+                states.magnetometer_ignored(),
+                states.magnetic_recovery_trigger(),
+                flags.magnetic_recovery(),
+            );
+        let heading = heading.min(180.0);
+        self.confidence = AttitudeConfidence {
+            tilt: tilt.min(90.0),
+            heading,
+            acceleration_age: self.acceleration_age,
+            magnetic_age: self.magnetic_age,
+            heading_valid: heading < settings.heading_limit,
+        };
+        self.confidence
+    }
+}
+
+impl Default for ConfidenceEstimator {
+    fn default() -> Self {
+        Self::new(ConfidenceSettings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Settings, Vector};
+
+    #[test]
+    fn heading_becomes_invalid_without_magnetometer() {
+        let mut ahrs = FusionAhrs::new();
+        let mut estimator = ConfidenceEstimator::default();
+        let accelerometer = Vector::new(0.0, 0.0, 1.0);
+        for _ in 0..500 {
+            ahrs.update(
+                Vector::ZERO,
+                accelerometer,
+                Vector::new(1.0, 0.0, 0.0),
+                0.01,
+            );
+            estimator.update(&ahrs, 0.01);
+        }
+        let initial = estimator.confidence();
+
+        // Act
+        for _ in 0..20000 {
+            ahrs.update_no_magnetometer(Vector::ZERO, accelerometer, 0.01);
+            estimator.update(&ahrs, 0.01);
+        }
+
+        let confidence = estimator.confidence();
+        assert_eq!(initial.tilt, 1.0);
+        assert!(initial.heading_valid);
+        assert!((confidence.magnetic_age - 200.0).abs() < 0.1);
+        assert_eq!(confidence.acceleration_age, 0.0);
+        assert!(confidence.heading > 20.0);
+        assert!(!confidence.heading_valid);
+        assert_eq!(confidence.tilt, initial.tilt);
+    }
+
+    #[test]
+    fn heading_uncertainty_follows_magnetic_recovery_trigger() {
+        let mut settings = Settings::new();
+        settings.set_magnetic_rejection(10.0);
+        settings.set_recovery_trigger_period(1000);
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(settings);
+        let mut estimator = ConfidenceEstimator::new(ConfidenceSettings {
+            gyroscope_drift: 0.0,
+            ..Default::default()
+        });
+        let accelerometer = Vector::new(0.0, 0.0, 1.0);
+        for _ in 0..500 {
+            ahrs.update(
+                Vector::ZERO,
+                accelerometer,
+                Vector::new(1.0, 0.0, 0.0),
+                0.01,
+            );
+            estimator.update(&ahrs, 0.01);
+        }
+        let disturbed = Vector::new(0.5, 0.866, 0.0);
+        let mut confidences = [AttitudeConfidence::UNKNOWN; 2];
+        let mut triggers = [0.0; 2];
+
+        // Act
+        for index in 0..500 {
+            ahrs.update(Vector::ZERO, accelerometer, disturbed, 0.01);
+            let confidence = estimator.update(&ahrs, 0.01);
+            if index == 99 || index == 499 {
+                confidences[(index == 499) as usize] = confidence;
+                triggers[(index == 499) as usize] =
+                    ahrs.get_internal_states().magnetic_recovery_trigger();
+            }
+        }
+
+        assert!(triggers[1] > triggers[0] && triggers[0] > 0.0);
+        assert!(confidences[1].heading > confidences[0].heading + 10.0);
+        assert_eq!(confidences[1].tilt, confidences[0].tilt);
+    }
+
+    #[test]
+    fn initialising_is_unknown() {
+        let mut ahrs = FusionAhrs::new();
+        let mut estimator = ConfidenceEstimator::default();
+
+        // Act
+        ahrs.update_no_magnetometer(Vector::ZERO, Vector::new(0.0, 0.0, 1.0), 0.01);
+        let confidence = estimator.update(&ahrs, 0.01);
+
+        assert_eq!(confidence.tilt, AttitudeConfidence::UNKNOWN.tilt);
+        assert!(!confidence.heading_valid);
+    }
+}
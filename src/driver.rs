@@ -0,0 +1,289 @@
+use core::convert::Infallible;
+
+use crate::{Ahrs, FusionAhrs, Quaternion, Settings, Vector};
+
+/// Error type shared by the sensor source traits of a driver.
+pub trait ErrorType {
+    /// Error returned when reading the sensor fails.
+    type Error;
+}
+
+/// Blocking gyroscope driver.
+pub trait GyroscopeSource: ErrorType {
+    /// Reads the gyroscope measurement in degrees per second.
+    fn read_gyroscope(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// Blocking accelerometer driver.
+pub trait AccelerometerSource: ErrorType {
+    /// Reads the accelerometer measurement in g.
+    fn read_accelerometer(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// Blocking magnetometer driver.
+pub trait MagnetometerSource: ErrorType {
+    /// Reads the magnetometer measurement in arbitrary units.
+    fn read_magnetometer(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// Async gyroscope driver.
+#[allow(async_fn_in_trait)]
+pub trait AsyncGyroscopeSource: ErrorType {
+    /// Reads the gyroscope measurement in degrees per second.
+    async fn read_gyroscope(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// Async accelerometer driver.
+#[allow(async_fn_in_trait)]
+pub trait AsyncAccelerometerSource: ErrorType {
+    /// Reads the accelerometer measurement in g.
+    async fn read_accelerometer(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// Async magnetometer driver.
+#[allow(async_fn_in_trait)]
+pub trait AsyncMagnetometerSource: ErrorType {
+    /// Reads the magnetometer measurement in arbitrary units.
+    async fn read_magnetometer(&mut self) -> Result<Vector, Self::Error>;
+}
+
+/// AHRS algorithm driven by a sensor source.
+///
+/// The source can be a single driver implementing several source traits or
+/// a struct that combines separate drivers.
+pub struct AhrsDriver<S, A = FusionAhrs> {
+    source: S,
+    ahrs: A,
+}
+
+impl<S> AhrsDriver<S> {
+    /// Create a new `AhrsDriver` instance.
+    pub fn new(source: S, settings: Settings) -> Self {
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(settings);
+        Self { source, ahrs }
+    }
+}
+
+impl<S, A: Ahrs> AhrsDriver<S, A> {
+    /// Create a new `AhrsDriver` instance with another AHRS algorithm.
+    pub fn with_ahrs(source: S, ahrs: A) -> Self {
+        Self { source, ahrs }
+    }
+
+    /// Returns the sensor source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the sensor source mutably, for example to configure it.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Returns the AHRS algorithm.
+    pub fn ahrs(&self) -> &A {
+        &self.ahrs
+    }
+
+    /// Returns the AHRS algorithm mutably.
+    pub fn ahrs_mut(&mut self) -> &mut A {
+        &mut self.ahrs
+    }
+
+    /// Releases the sensor source.
+    pub fn release(self) -> S {
+        self.source
+    }
+}
+
+impl<S: GyroscopeSource + AccelerometerSource, A: Ahrs> AhrsDriver<S, A> {
+    /// Reads the gyroscope and accelerometer and updates the AHRS algorithm
+    /// without a magnetometer. Returns the updated quaternion.
+    ///
+    /// Arguments:
+    /// - `delta_time`: Delta time in seconds.
+    pub fn poll(&mut self, delta_time: f32) -> Result<Quaternion, S::Error> {
+        let gyroscope = self.source.read_gyroscope()?;
+        let accelerometer = self.source.read_accelerometer()?;
+        self.ahrs
+            .update_no_magnetometer(gyroscope, accelerometer, delta_time);
+        Ok(self.ahrs.quaternion())
+    }
+}
+
+impl<S: GyroscopeSource + AccelerometerSource + MagnetometerSource, A: Ahrs> AhrsDriver<S, A> {
+    /// Reads the gyroscope, accelerometer, and magnetometer and updates the
+    /// AHRS algorithm. Returns the updated quaternion.
+    ///
+    /// Arguments:
+    /// - `delta_time`: Delta time in seconds.
+    pub fn poll_with_magnetometer(&mut self, delta_time: f32) -> Result<Quaternion, S::Error> {
+        let gyroscope = self.source.read_gyroscope()?;
+        let accelerometer = self.source.read_accelerometer()?;
+        let magnetometer = self.source.read_magnetometer()?;
+        self.ahrs
+            .update(gyroscope, accelerometer, magnetometer, delta_time);
+        Ok(self.ahrs.quaternion())
+    }
+}
+
+impl<S: AsyncGyroscopeSource + AsyncAccelerometerSource, A: Ahrs> AhrsDriver<S, A> {
+    /// Async version of [`poll`](AhrsDriver::poll).
+    pub async fn poll_async(&mut self, delta_time: f32) -> Result<Quaternion, S::Error> {
+        let gyroscope = self.source.read_gyroscope().await?;
+        let accelerometer = self.source.read_accelerometer().await?;
+        self.ahrs
+            .update_no_magnetometer(gyroscope, accelerometer, delta_time);
+        Ok(self.ahrs.quaternion())
+    }
+}
+
+impl<S: AsyncGyroscopeSource + AsyncAccelerometerSource + AsyncMagnetometerSource, A: Ahrs>
+    AhrsDriver<S, A>
+{
+    /// Async version of
+    /// [`poll_with_magnetometer`](AhrsDriver::poll_with_magnetometer).
+    pub async fn poll_with_magnetometer_async(
+        &mut self,
+        delta_time: f32,
+    ) -> Result<Quaternion, S::Error> {
+        let gyroscope = self.source.read_gyroscope().await?;
+        let accelerometer = self.source.read_accelerometer().await?;
+        let magnetometer = self.source.read_magnetometer().await?;
+        self.ahrs
+            .update(gyroscope, accelerometer, magnetometer, delta_time);
+        Ok(self.ahrs.quaternion())
+    }
+}
+
+/// Sensor source returning fixed measurements, for testing.
+///
+/// Implements both the blocking and async source traits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MockSource {
+    /// Gyroscope measurement in degrees per second.
+    pub gyroscope: Vector,
+    /// Accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Magnetometer measurement in arbitrary units.
+    pub magnetometer: Vector,
+    /// Number of sensor reads.
+    pub reads: u32,
+}
+
+impl Default for MockSource {
+    fn default() -> Self {
+        Self {
+            gyroscope: Vector::ZERO,
+            accelerometer: Vector::new(0.0, 0.0, 1.0),
+            magnetometer: Vector::new(1.0, 0.0, 0.0),
+            reads: 0,
+        }
+    }
+}
+
+impl MockSource {
+    /// Create a new `MockSource` instance for a stationary, level sensor
+    /// pointing north.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&mut self, vector: Vector) -> Result<Vector, Infallible> {
+        self.reads += 1;
+        Ok(vector)
+    }
+}
+
+impl ErrorType for MockSource {
+    type Error = Infallible;
+}
+
+impl GyroscopeSource for MockSource {
+    fn read_gyroscope(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.gyroscope)
+    }
+}
+
+impl AccelerometerSource for MockSource {
+    fn read_accelerometer(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.accelerometer)
+    }
+}
+
+impl MagnetometerSource for MockSource {
+    fn read_magnetometer(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.magnetometer)
+    }
+}
+
+impl AsyncGyroscopeSource for MockSource {
+    async fn read_gyroscope(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.gyroscope)
+    }
+}
+
+impl AsyncAccelerometerSource for MockSource {
+    async fn read_accelerometer(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.accelerometer)
+    }
+}
+
+impl AsyncMagnetometerSource for MockSource {
+    async fn read_magnetometer(&mut self) -> Result<Vector, Self::Error> {
+        self.read(self.magnetometer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use super::*;
+
+    /// Runs a future that never waits to completion.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn poll_reads_gyroscope_and_accelerometer() {
+        let mut driver = AhrsDriver::new(MockSource::new(), Settings::new());
+
+        // Act
+        let quaternion = driver.poll(0.01).unwrap();
+
+        assert_eq!(driver.source().reads, 2);
+        assert!(quaternion.angle_to(Quaternion::IDENTITY) < 0.01);
+    }
+
+    #[test]
+    fn async_poll_reads_magnetometer() {
+        let source = MockSource {
+            magnetometer: Vector::new(0.0, 1.0, 0.0),
+            ..Default::default()
+        };
+        let mut driver = AhrsDriver::new(source, Settings::new());
+
+        // Act
+        for _ in 0..500 {
+            block_on(driver.poll_with_magnetometer_async(0.01)).unwrap();
+        }
+
+        let quaternion = driver.ahrs().get_quaternion();
+        assert_eq!(driver.release().reads, 1500);
+        let heading = quaternion.to_euler().yaw;
+        assert!((heading + 90.0).abs() < 1.0);
+    }
+}
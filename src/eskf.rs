@@ -0,0 +1,645 @@
+use crate::{Ahrs, Convention, Matrix, Quaternion, Vector};
+
+/// Error-state Kalman filter settings. The process and measurement noise are
+/// derived from the noise specifications of the sensors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct EskfSettings {
+    /// Earth axes convention.
+    pub convention: Convention,
+    /// Sample rate in Hz, which sets the measurement noise bandwidth.
+    pub sample_rate: f32,
+    /// Gyroscope noise density in degrees per second per square root hertz.
+    pub gyroscope_noise: f32,
+    /// Gyroscope bias random walk in degrees per second per square root
+    /// second.
+    pub gyroscope_bias_random_walk: f32,
+    /// Accelerometer noise density in g per square root hertz. Increase it to
+    /// account for linear acceleration.
+    pub accelerometer_noise: f32,
+    /// Magnetometer noise density as a fraction of the field strength per
+    /// square root hertz.
+    pub magnetometer_noise: f32,
+    /// Angle in degrees between the measured and predicted accelerometer
+    /// directions above which the measurement is rejected.
+    pub acceleration_rejection: f32,
+    /// Angle in degrees between the measured and predicted magnetometer
+    /// directions above which the measurement is rejected.
+    pub magnetic_rejection: f32,
+    /// Initial attitude standard deviation in degrees about each axis after
+    /// the attitude has been initialised from the first measurement.
+    pub initial_attitude_uncertainty: f32,
+    /// Initial gyroscope bias standard deviation in degrees per second.
+    pub initial_bias_uncertainty: f32,
+}
+
+impl Default for EskfSettings {
+    fn default() -> Self {
+        Self {
+            convention: Convention::NorthWestUp,
+            sample_rate: 100.0,
+            gyroscope_noise: 0.01,
+            gyroscope_bias_random_walk: 0.001,
+            accelerometer_noise: 0.002,
+            magnetometer_noise: 0.005,
+            acceleration_rejection: 10.0,
+            magnetic_rejection: 20.0,
+            initial_attitude_uncertainty: 5.0,
+            initial_bias_uncertainty: 1.0,
+        }
+    }
+}
+
+/// Error-state extended Kalman filter estimating the attitude and the
+/// gyroscope bias with their covariance.
+///
+/// The error state is the attitude error in the sensor frame and the
+/// gyroscope bias error. The attitude is initialised from the first
+/// accelerometer and magnetometer measurements.
+#[derive(Debug, Clone, Copy)]
+pub struct Eskf {
+    settings: EskfSettings,
+    initialised: bool,
+    quaternion: Quaternion,
+    bias: Vector,
+    /// Covariance blocks: attitude, attitude-bias, and bias, in radians.
+    attitude_covariance: Matrix,
+    cross_covariance: Matrix,
+    bias_covariance: Matrix,
+    accelerometer: Vector,
+}
+
+impl Eskf {
+    /// Create a new `Eskf` instance.
+    pub fn new(settings: EskfSettings) -> Self {
+        let bias_variance = square(settings.initial_bias_uncertainty.to_radians());
+        Self {
+            settings,
+            initialised: false,
+            quaternion: Quaternion::IDENTITY,
+            bias: Vector::ZERO,
+            attitude_covariance: Matrix::IDENTITY * square(core::f32::consts::PI),
+            cross_covariance: ZERO,
+            bias_covariance: Matrix::IDENTITY * bias_variance,
+            accelerometer: Vector::ZERO,
+        }
+    }
+
+    /// Returns the settings.
+    pub fn settings(&self) -> &EskfSettings {
+        &self.settings
+    }
+
+    /// Sets the settings. The noise settings apply from the next update.
+    pub fn set_settings(&mut self, settings: EskfSettings) {
+        self.settings = settings;
+    }
+
+    /// Returns true until the attitude has been initialised from the first
+    /// accelerometer measurement.
+    pub fn initialising(&self) -> bool {
+        !self.initialised
+    }
+
+    /// Returns the gyroscope bias estimate in degrees per second.
+    pub fn gyroscope_bias(&self) -> Vector {
+        radians_to_degrees(self.bias)
+    }
+
+    /// Returns the gyroscope bias covariance in degrees per second squared.
+    pub fn gyroscope_bias_covariance(&self) -> Matrix {
+        self.bias_covariance * square(180.0 / core::f32::consts::PI)
+    }
+
+    /// Returns the attitude covariance in radians squared about the Earth
+    /// axes, as used by ROS `sensor_msgs/Imu`.
+    pub fn attitude_covariance(&self) -> Matrix {
+        let rotation = self.quaternion.to_matrix();
+        rotation * self.attitude_covariance * rotation.transpose()
+    }
+
+    /// Returns the attitude standard deviation in degrees about each Earth
+    /// axis.
+    pub fn attitude_uncertainty(&self) -> Vector {
+        let covariance = self.attitude_covariance();
+        Vector::new(
+            libm::sqrtf(covariance.xx).to_degrees(),
+            libm::sqrtf(covariance.yy).to_degrees(),
+            libm::sqrtf(covariance.zz).to_degrees(),
+        )
+    }
+
+    /// Propagates the state and covariance with the gyroscope measurement.
+    fn predict(&mut self, gyroscope: Vector, delta_time: f32) {
+        let rate = degrees_to_radians(gyroscope) - self.bias;
+        let rotation = exp(rate * delta_time);
+        self.quaternion = (self.quaternion * rotation).normalise();
+
+        // F = [Φ, -I dt; 0, I]
+        let transition = rotation.to_matrix().transpose();
+        let (a, b, c) = (
+            self.attitude_covariance,
+            self.cross_covariance,
+            self.bias_covariance,
+        );
+        let attitude_noise = square(self.settings.gyroscope_noise.to_radians()) * delta_time;
+        let bias_noise = square(self.settings.gyroscope_bias_random_walk.to_radians()) * delta_time;
+        self.attitude_covariance = transition * a * transition.transpose()
+            - (transition * b + b.transpose() * transition.transpose()) * delta_time
+            + c * square(delta_time)
+            + Matrix::IDENTITY * attitude_noise;
+        self.cross_covariance = transition * b - c * delta_time;
+        self.bias_covariance = c + Matrix::IDENTITY * bias_noise;
+    }
+
+    /// Corrects the state with a measured direction in the sensor frame of a
+    /// reference direction in the Earth frame, unless the measurement is
+    /// rejected.
+    ///
+    /// The projection selects the attitude error axes in the sensor frame
+    /// that the measurement observes and corrects. The covariance is updated
+    /// in Joseph form, which remains valid for the projected gain.
+    fn correct(
+        &mut self,
+        reference: Vector,
+        measurement: Vector,
+        projection: Matrix,
+        noise: f32,
+        rejection: f32,
+    ) {
+        let predicted = self.quaternion.to_matrix().transpose() * reference;
+        let residual = measurement - predicted;
+        if libm::acosf(measurement.dot(predicted).clamp(-1.0, 1.0)) > rejection.to_radians() {
+            return;
+        }
+
+        // H = [X, 0] where X = [predicted]× projection
+        let observation = skew(predicted) * projection;
+        let (a, b, c) = (
+            self.attitude_covariance,
+            self.cross_covariance,
+            self.bias_covariance,
+        );
+        let variance = square(noise) * 0.5 * self.settings.sample_rate;
+        let innovation = observation * a * observation.transpose() + Matrix::IDENTITY * variance;
+        let Some(inverse) = innovation.inverse() else {
+            return;
+        };
+        let attitude_gain = projection * a * observation.transpose() * inverse;
+        let bias_gain = projection * b.transpose() * observation.transpose() * inverse;
+
+        // Injection
+        self.quaternion = (self.quaternion * exp(attitude_gain * residual)).normalise();
+        self.bias += bias_gain * residual;
+
+        // P = (I - K H) P (I - K H)' + K R K'
+        let attitude_factor = Matrix::IDENTITY - attitude_gain * observation;
+        let bias_factor = ZERO - bias_gain * observation;
+        let (attitude_a, attitude_b) = (attitude_factor * a, attitude_factor * b);
+        let (bias_a, bias_b) = (bias_factor * a + b.transpose(), bias_factor * b + c);
+        self.attitude_covariance = symmetric(
+            attitude_a * attitude_factor.transpose()
+                + attitude_gain * attitude_gain.transpose() * variance,
+        );
+        self.cross_covariance = attitude_a * bias_factor.transpose()
+            + attitude_b
+            + attitude_gain * bias_gain.transpose() * variance;
+        self.bias_covariance = symmetric(
+            bias_a * bias_factor.transpose()
+                + bias_b
+                + bias_gain * bias_gain.transpose() * variance,
+        );
+    }
+
+    /// Initialises the attitude from the accelerometer and, if available,
+    /// the magnetometer.
+    fn initialise(&mut self, accelerometer: Vector, magnetometer: Option<Vector>) {
+        let (up, north) = references(self.settings.convention);
+        let sensor_up = accelerometer.normalise();
+        let sensor_north = magnetometer
+            .map(|magnetometer| magnetometer - sensor_up * magnetometer.dot(sensor_up))
+            .filter(|north| !north.is_zero());
+        self.quaternion = match sensor_north {
+            Some(sensor_north) => {
+                let sensor_north = sensor_north.normalise();
+                let earth = Matrix::from_columns(up, north, up.cross(north));
+                let sensor =
+                    Matrix::from_columns(sensor_up, sensor_north, sensor_up.cross(sensor_north));
+                Quaternion::from_matrix(earth * sensor.transpose())
+            }
+            None => {
+                let axis = sensor_up.cross(up);
+                let angle = libm::atan2f(axis.magnitude(), sensor_up.dot(up)).to_degrees();
+                if axis.is_zero() {
+                    Quaternion::from_axis_angle(north, angle)
+                } else {
+                    Quaternion::from_axis_angle(axis, angle)
+                }
+            }
+        };
+        self.attitude_covariance =
+            Matrix::IDENTITY * square(self.settings.initial_attitude_uncertainty.to_radians());
+        self.cross_covariance = ZERO;
+        self.initialised = true;
+    }
+
+    /// Corrects the heading with the magnetometer measurement. The magnetic
+    /// field reference has the inclination of the measurement and the
+    /// correction of the attitude and gyroscope bias is limited to the Earth
+    /// vertical so that magnetic disturbances do not affect the tilt.
+    fn correct_magnetometer(&mut self, magnetometer: Vector) {
+        let (up, north) = references(self.settings.convention);
+        let magnetometer = magnetometer.normalise();
+        let field = self.quaternion.rotate(magnetometer);
+        let vertical = field.dot(up);
+        let horizontal = (field - up * vertical).magnitude();
+        let sensor_up = self.quaternion.to_matrix().transpose() * up;
+        self.correct(
+            up * vertical + north * horizontal,
+            magnetometer,
+            outer(sensor_up),
+            self.settings.magnetometer_noise,
+            self.settings.magnetic_rejection,
+        );
+    }
+}
+
+impl Default for Eskf {
+    fn default() -> Self {
+        Self::new(EskfSettings::default())
+    }
+}
+
+impl Ahrs for Eskf {
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    ) {
+        if magnetometer.is_zero() {
+            return self.update_no_magnetometer(gyroscope, accelerometer, delta_time);
+        }
+        self.accelerometer = accelerometer;
+        if !self.initialised {
+            if !accelerometer.is_zero() {
+                self.initialise(accelerometer, Some(magnetometer));
+            }
+            return;
+        }
+        self.predict(gyroscope, delta_time);
+        if !accelerometer.is_zero() {
+            let (up, _) = references(self.settings.convention);
+            let settings = self.settings;
+            self.correct(
+                up,
+                accelerometer.normalise(),
+                Matrix::IDENTITY,
+                settings.accelerometer_noise,
+                settings.acceleration_rejection,
+            );
+        }
+        self.correct_magnetometer(magnetometer);
+    }
+
+    fn update_no_magnetometer(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        delta_time: f32,
+    ) {
+        self.accelerometer = accelerometer;
+        if !self.initialised {
+            if !accelerometer.is_zero() {
+                self.initialise(accelerometer, None);
+            }
+            return;
+        }
+        self.predict(gyroscope, delta_time);
+        if !accelerometer.is_zero() {
+            let (up, _) = references(self.settings.convention);
+            self.correct(
+                up,
+                accelerometer.normalise(),
+                Matrix::IDENTITY,
+                self.settings.accelerometer_noise,
+                self.settings.acceleration_rejection,
+            );
+        }
+    }
+
+    /// Synthesises a magnetometer measurement of the north reference of the
+    /// convention from the heading and the current tilt, and calls
+    /// [`update`](Ahrs::update).
+    fn update_external_heading(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        heading: f32,
+        delta_time: f32,
+    ) {
+        let (_, north) = references(self.settings.convention);
+        let vertical = Vector::new(0.0, 0.0, 1.0);
+        let level = self.quaternion.twist(vertical).conjugate() * self.quaternion;
+        let quaternion = Quaternion::from_axis_angle(vertical, heading) * level;
+        let magnetometer = quaternion.conjugate().rotate(north);
+        self.update(gyroscope, accelerometer, magnetometer, delta_time);
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    fn linear_acceleration(&self) -> Vector {
+        let (up, _) = references(self.settings.convention);
+        self.accelerometer - self.quaternion.to_matrix().transpose() * up
+    }
+
+    fn earth_acceleration(&self) -> Vector {
+        let (up, _) = references(self.settings.convention);
+        self.quaternion.rotate(self.accelerometer) - up
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.settings);
+    }
+}
+
+const ZERO: Matrix = Matrix::from_diagonal(Vector::ZERO);
+
+/// Returns the directions in the Earth frame of the accelerometer
+/// measurement when stationary and of the horizontal magnetic field.
+fn references(convention: Convention) -> (Vector, Vector) {
+    match convention {
+        Convention::NorthWestUp => (Vector::new(0.0, 0.0, 1.0), Vector::new(1.0, 0.0, 0.0)),
+        Convention::EastNorthUp => (Vector::new(0.0, 0.0, 1.0), Vector::new(0.0, 1.0, 0.0)),
+        Convention::NorthWestDown => (Vector::new(0.0, 0.0, -1.0), Vector::new(1.0, 0.0, 0.0)),
+    }
+}
+
+/// Returns the quaternion of a rotation vector in radians.
+fn exp(rotation: Vector) -> Quaternion {
+    let angle = rotation.magnitude();
+    if angle == 0.0 {
+        return Quaternion::IDENTITY;
+    }
+    Quaternion::from_axis_angle(rotation, angle.to_degrees())
+}
+
+fn skew(v: Vector) -> Matrix {
+    Matrix::from_rows(
+        Vector::new(0.0, -v.z, v.y),
+        Vector::new(v.z, 0.0, -v.x),
+        Vector::new(-v.y, v.x, 0.0),
+    )
+}
+
+fn outer(v: Vector) -> Matrix {
+    Matrix::from_columns(v * v.x, v * v.y, v * v.z)
+}
+
+fn symmetric(matrix: Matrix) -> Matrix {
+    (matrix + matrix.transpose()) * 0.5
+}
+
+fn square(value: f32) -> f32 {
+    value * value
+}
+
+fn degrees_to_radians(vector: Vector) -> Vector {
+    vector * (core::f32::consts::PI / 180.0)
+}
+
+fn radians_to_degrees(vector: Vector) -> Vector {
+    vector * (180.0 / core::f32::consts::PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Segment, SensorErrors, SimulationConfig, Simulator};
+
+    fn simulation(bias: Vector) -> SimulationConfig {
+        SimulationConfig {
+            initial_quaternion: Quaternion::from_axis_angle(Vector::new(1.0, -1.0, 0.5), 40.0),
+            gyroscope: SensorErrors {
+                white_noise: 0.01,
+                bias,
+                ..Default::default()
+            },
+            accelerometer: SensorErrors {
+                white_noise: 0.002,
+                ..Default::default()
+            },
+            magnetometer: SensorErrors {
+                white_noise: 0.2,
+                ..Default::default()
+            },
+            seed: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn attitude_and_bias_converge() {
+        let trajectory = [
+            Segment::stationary(20.0),
+            Segment::rotation(2.0, Vector::new(0.0, 0.0, 45.0)),
+            Segment::rotation(2.0, Vector::new(30.0, 0.0, 0.0)),
+        ];
+        let bias = Vector::new(0.5, -0.3, 0.2);
+        let mut eskf = Eskf::default();
+        let mut last = None;
+
+        // Act
+        for sample in Simulator::new(simulation(bias), &trajectory) {
+            eskf.update(
+                sample.gyroscope,
+                sample.accelerometer,
+                sample.magnetometer,
+                0.01,
+            );
+            last = Some(sample);
+        }
+
+        let last = last.unwrap();
+        assert!(!eskf.initialising());
+        assert!(eskf.quaternion().angle_to(last.quaternion) < 1.0);
+        assert!((eskf.gyroscope_bias() - bias).magnitude() < 0.1);
+        let uncertainty = eskf.attitude_uncertainty();
+        assert!(uncertainty.x < 1.0 && uncertainty.y < 1.0 && uncertainty.z < 1.0);
+        let covariance = eskf.attitude_covariance();
+        assert!((covariance.xy - covariance.yx).abs() < 1e-9);
+    }
+
+    #[test]
+    fn external_heading_follows_convention() {
+        for convention in [
+            Convention::NorthWestUp,
+            Convention::EastNorthUp,
+            Convention::NorthWestDown,
+        ] {
+            let mut eskf = Eskf::new(EskfSettings {
+                convention,
+                ..Default::default()
+            });
+            let accelerometer = match convention {
+                Convention::NorthWestDown => Vector::new(0.0, 0.0, -1.0),
+                _ => Vector::new(0.0, 0.0, 1.0),
+            };
+
+            // Act
+            for _ in 0..100 {
+                eskf.update_external_heading(Vector::ZERO, accelerometer, 30.0, 0.01);
+            }
+
+            assert!((eskf.quaternion().to_euler().yaw - 30.0).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn magnetometer_does_not_correct_tilt() {
+        let mut eskf = Eskf::default();
+        eskf.initialise(
+            Vector::new(0.0, 0.0, 1.0),
+            Some(Vector::new(0.5, 0.0, -0.866)),
+        );
+        let (up, _) = references(eskf.settings.convention);
+        let tilt = eskf.quaternion.to_matrix().transpose() * up;
+        let covariance = eskf.attitude_covariance();
+
+        // Act
+        eskf.correct_magnetometer(Vector::new(0.49, -0.087, -0.866));
+
+        let corrected = eskf.attitude_covariance();
+        assert!((eskf.quaternion.to_matrix().transpose() * up - tilt).magnitude() < 1e-6);
+        assert!((corrected.xx - covariance.xx).abs() < 1e-7);
+        assert!((corrected.yy - covariance.yy).abs() < 1e-7);
+        assert!(corrected.zz < covariance.zz);
+        assert!(eskf.quaternion.to_euler().yaw > 1.0);
+    }
+
+    #[test]
+    fn magnetometer_does_not_correct_tilt_with_correlated_covariance() {
+        let mut eskf = Eskf::default();
+        eskf.initialise(
+            Vector::new(0.0, 0.0, 1.0),
+            Some(Vector::new(0.5, 0.0, -0.866)),
+        );
+        eskf.attitude_covariance = Matrix::from_rows(
+            Vector::new(0.004, 0.001, 0.003),
+            Vector::new(0.001, 0.005, -0.002),
+            Vector::new(0.003, -0.002, 0.008),
+        );
+        eskf.cross_covariance = Matrix::from_diagonal(Vector::new(1e-4, 1e-4, 1e-4));
+        let (up, _) = references(eskf.settings.convention);
+        let tilt = eskf.quaternion.to_matrix().transpose() * up;
+        let heading = eskf.attitude_covariance().zz;
+
+        // Act
+        eskf.correct_magnetometer(Vector::new(0.49, -0.087, -0.866));
+
+        assert!((eskf.quaternion.to_matrix().transpose() * up - tilt).magnitude() < 1e-6);
+        assert!(eskf.quaternion.to_euler().yaw > 1.0);
+        assert!(eskf.gyroscope_bias().x.abs() < 1e-6 && eskf.gyroscope_bias().y.abs() < 1e-6);
+        let covariance = eskf.attitude_covariance();
+        assert!(covariance.zz < heading);
+        assert!(covariance.xx > 0.0 && covariance.yy > 0.0);
+    }
+
+    #[test]
+    fn predicted_covariance_matches_full_transition() {
+        let mut eskf = Eskf::new(EskfSettings {
+            gyroscope_noise: 0.0,
+            gyroscope_bias_random_walk: 0.0,
+            ..Default::default()
+        });
+        let a = Matrix::from_rows(
+            Vector::new(0.04, 0.01, -0.02),
+            Vector::new(0.01, 0.05, 0.015),
+            Vector::new(-0.02, 0.015, 0.06),
+        );
+        let b = Matrix::from_rows(
+            Vector::new(0.003, -0.001, 0.002),
+            Vector::new(0.004, 0.002, -0.003),
+            Vector::new(-0.002, 0.001, 0.005),
+        );
+        let c = Matrix::from_diagonal(Vector::new(0.01, 0.02, 0.03));
+        eskf.initialised = true;
+        eskf.attitude_covariance = a;
+        eskf.cross_covariance = b;
+        eskf.bias_covariance = c;
+        let (gyroscope, delta_time) = (Vector::new(300.0, -200.0, 500.0), 0.1);
+
+        // Act
+        eskf.predict(gyroscope, delta_time);
+
+        let phi = exp(degrees_to_radians(gyroscope) * delta_time)
+            .to_matrix()
+            .transpose();
+        let mut f = [[0.0; 6]; 6];
+        let mut p = [[0.0; 6]; 6];
+        for row in 0..3 {
+            for column in 0..3 {
+                f[row][column] = element(phi, row, column);
+                p[row][column] = element(a, row, column);
+                p[row][column + 3] = element(b, row, column);
+                p[row + 3][column] = element(b, column, row);
+                p[row + 3][column + 3] = element(c, row, column);
+            }
+            f[row][row + 3] = -delta_time;
+            f[row + 3][row + 3] = 1.0;
+        }
+        let expected = multiply(multiply(f, p), transpose(f));
+        for row in 0..3 {
+            for column in 0..3 {
+                let attitude = element(eskf.attitude_covariance, row, column);
+                let cross = element(eskf.cross_covariance, row, column);
+                let bias = element(eskf.bias_covariance, row, column);
+                assert!((attitude - expected[row][column]).abs() < 1e-6);
+                assert!((cross - expected[row][column + 3]).abs() < 1e-6);
+                assert!((bias - expected[row + 3][column + 3]).abs() < 1e-6);
+            }
+        }
+    }
+
+    fn element(matrix: Matrix, row: usize, column: usize) -> f32 {
+        let row = matrix.rows()[row];
+        [row.x, row.y, row.z][column]
+    }
+
+    fn multiply(a: [[f32; 6]; 6], b: [[f32; 6]; 6]) -> [[f32; 6]; 6] {
+        core::array::from_fn(|row| {
+            core::array::from_fn(|column| {
+                (0..6).map(|index| a[row][index] * b[index][column]).sum()
+            })
+        })
+    }
+
+    fn transpose(a: [[f32; 6]; 6]) -> [[f32; 6]; 6] {
+        core::array::from_fn(|row| core::array::from_fn(|column| a[column][row]))
+    }
+
+    #[test]
+    fn heading_uncertainty_grows_without_magnetometer() {
+        let trajectory = [Segment::stationary(10.0)];
+        let mut eskf = Eskf::default();
+        let mut uncertainties = [Vector::ZERO; 2];
+
+        // Act
+        for (index, sample) in Simulator::new(simulation(Vector::ZERO), &trajectory).enumerate() {
+            eskf.update_no_magnetometer(sample.gyroscope, sample.accelerometer, 0.01);
+            if index == 100 || index == 999 {
+                uncertainties[(index == 999) as usize] = eskf.attitude_uncertainty();
+            }
+        }
+
+        let [early, late] = uncertainties;
+        assert!(late.x < 0.5 && late.y < 0.5);
+        assert!(late.z > early.z);
+        assert!(late.z > 4.0);
+    }
+}
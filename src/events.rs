@@ -0,0 +1,273 @@
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::Ahrs;
+
+/// Set of AHRS algorithm events, each a transition of a flag or internal
+/// state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AhrsEvents(u16);
+
+impl AhrsEvents {
+    /// No events.
+    pub const NONE: AhrsEvents = AhrsEvents(0);
+    /// Initialisation completed.
+    pub const INITIALISED: AhrsEvents = AhrsEvents(1 << 0);
+    /// Angular rate recovery started.
+    pub const ANGULAR_RATE_RECOVERY_STARTED: AhrsEvents = AhrsEvents(1 << 1);
+    /// Angular rate recovery ended.
+    pub const ANGULAR_RATE_RECOVERY_ENDED: AhrsEvents = AhrsEvents(1 << 2);
+    /// Acceleration recovery started.
+    pub const ACCELERATION_RECOVERY_STARTED: AhrsEvents = AhrsEvents(1 << 3);
+    /// Acceleration recovery ended.
+    pub const ACCELERATION_RECOVERY_ENDED: AhrsEvents = AhrsEvents(1 << 4);
+    /// Magnetic recovery started.
+    pub const MAGNETIC_RECOVERY_STARTED: AhrsEvents = AhrsEvents(1 << 5);
+    /// Magnetic recovery ended.
+    pub const MAGNETIC_RECOVERY_ENDED: AhrsEvents = AhrsEvents(1 << 6);
+    /// Accelerometer started being ignored.
+    pub const ACCELEROMETER_IGNORED: AhrsEvents = AhrsEvents(1 << 7);
+    /// Accelerometer used again after being ignored.
+    pub const ACCELEROMETER_ACCEPTED: AhrsEvents = AhrsEvents(1 << 8);
+    /// Magnetometer started being ignored.
+    pub const MAGNETOMETER_IGNORED: AhrsEvents = AhrsEvents(1 << 9);
+    /// Magnetometer used again after being ignored.
+    pub const MAGNETOMETER_ACCEPTED: AhrsEvents = AhrsEvents(1 << 10);
+
+    const COUNT: usize = 11;
+
+    /// Returns the raw bits.
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Returns true if there are no events.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all events of `other` are in the set.
+    pub const fn contains(self, other: AhrsEvents) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if any event of `other` is in the set.
+    pub const fn intersects(self, other: AhrsEvents) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns an iterator over the single events in the set.
+    pub fn iter(self) -> impl Iterator<Item = AhrsEvents> {
+        (0..Self::COUNT)
+            .map(|bit| AhrsEvents(1 << bit))
+            .filter(move |event| self.contains(*event))
+    }
+
+    fn index(self) -> usize {
+        self.0.trailing_zeros() as usize
+    }
+}
+
+impl BitOr for AhrsEvents {
+    type Output = AhrsEvents;
+
+    fn bitor(self, rhs: AhrsEvents) -> AhrsEvents {
+        AhrsEvents(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for AhrsEvents {
+    fn bitor_assign(&mut self, rhs: AhrsEvents) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Single AHRS algorithm event with its timestamp.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AhrsEvent {
+    /// Event.
+    pub event: AhrsEvents,
+    /// Time in seconds of the update in which the event occurred.
+    pub time: f32,
+    /// For the events that end a condition, the time in seconds since the
+    /// condition started. Zero otherwise.
+    pub duration: f32,
+}
+
+/// Monitor of the AHRS algorithm flags and internal states that detects
+/// events.
+///
+/// The monitor starts in the state of a reset AHRS algorithm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventMonitor {
+    conditions: [bool; CONDITIONS.len()],
+    started: [f32; CONDITIONS.len()],
+    last: [Option<f32>; AhrsEvents::COUNT],
+}
+
+/// Start and end events of each monitored flag or internal state.
+const CONDITIONS: [(AhrsEvents, AhrsEvents); 6] = [
+    (AhrsEvents::NONE, AhrsEvents::INITIALISED),
+    (
+        AhrsEvents::ANGULAR_RATE_RECOVERY_STARTED,
+        AhrsEvents::ANGULAR_RATE_RECOVERY_ENDED,
+    ),
+    (
+        AhrsEvents::ACCELERATION_RECOVERY_STARTED,
+        AhrsEvents::ACCELERATION_RECOVERY_ENDED,
+    ),
+    (
+        AhrsEvents::MAGNETIC_RECOVERY_STARTED,
+        AhrsEvents::MAGNETIC_RECOVERY_ENDED,
+    ),
+    (
+        AhrsEvents::ACCELEROMETER_IGNORED,
+        AhrsEvents::ACCELEROMETER_ACCEPTED,
+    ),
+    (
+        AhrsEvents::MAGNETOMETER_IGNORED,
+        AhrsEvents::MAGNETOMETER_ACCEPTED,
+    ),
+];
+
+impl EventMonitor {
+    /// Create a new `EventMonitor` instance.
+    pub fn new() -> Self {
+        Self {
+            conditions: [true, false, false, false, false, false],
+            started: [0.0; CONDITIONS.len()],
+            last: [None; AhrsEvents::COUNT],
+        }
+    }
+
+    /// Detects the events since the previous update. Call after each update
+    /// of the AHRS algorithm.
+    ///
+    /// Arguments:
+    /// - `ahrs`: AHRS algorithm.
+    /// - `time`: Time in seconds.
+    pub fn update(&mut self, ahrs: &(impl Ahrs + ?Sized), time: f32) -> AhrsEvents {
+        self.update_with(ahrs, time, |_| {})
+    }
+
+    /// Detects the events since the previous update and calls the observer
+    /// for each event.
+    ///
+    /// Arguments:
+    /// - `ahrs`: AHRS algorithm.
+    /// - `time`: Time in seconds.
+    /// - `observer`: Called with each event in the order of the event bits.
+    pub fn update_with(
+        &mut self,
+        ahrs: &(impl Ahrs + ?Sized),
+        time: f32,
+        mut observer: impl FnMut(AhrsEvent),
+    ) -> AhrsEvents {
+        let flags = ahrs.flags();
+        let states = ahrs.internal_states();
+        let conditions = [
+            flags.initialising(),
+            flags.angular_rate_recovery(),
+            flags.acceleration_recovery(),
+            flags.magnetic_recovery(),
+            states.accelerometer_ignored(),
+            states.magnetometer_ignored(),
+        ];
+        let mut events = AhrsEvents::NONE;
+        for (index, (&active, (start, end))) in conditions.iter().zip(CONDITIONS).enumerate() {
+            if active == self.conditions[index] {
+                continue;
+            }
+            self.conditions[index] = active;
+            let (event, duration) = if active {
+                self.started[index] = time;
+                (start, 0.0)
+            } else {
+                (end, time - self.started[index])
+            };
+            if event.is_empty() {
+                continue;
+            }
+            events |= event;
+            self.last[event.index()] = Some(time);
+            observer(AhrsEvent {
+                event,
+                time,
+                duration,
+            });
+        }
+        events
+    }
+
+    /// Returns the time in seconds of the last occurrence of a single event,
+    /// if it occurred.
+    pub fn last(&self, event: AhrsEvents) -> Option<f32> {
+        self.last.get(event.index()).copied().flatten()
+    }
+}
+
+impl Default for EventMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FusionAhrs, Settings, Vector};
+
+    #[test]
+    fn transitions_are_reported_once_with_timestamps() {
+        let mut settings = Settings::new();
+        settings.set_acceleration_rejection(10.0);
+        settings.set_recovery_trigger_period(1000);
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(settings);
+        let mut monitor = EventMonitor::new();
+        let mut observed = [None; 2];
+        let mut events = AhrsEvents::NONE;
+
+        // Act
+        for index in 0..600 {
+            let time = index as f32 * 0.01;
+            let accelerometer = if index < 400 {
+                Vector::new(0.0, 0.0, 1.0)
+            } else {
+                Vector::new(1.0, 0.0, 1.0)
+            };
+            ahrs.update_no_magnetometer(Vector::ZERO, accelerometer, 0.01);
+            events |= monitor.update_with(&ahrs, time, |event| {
+                if event.event == AhrsEvents::INITIALISED {
+                    observed[0] = Some(event);
+                } else if event.event == AhrsEvents::ACCELEROMETER_IGNORED {
+                    observed[1] = Some(event);
+                }
+            });
+        }
+
+        assert!(events.contains(AhrsEvents::INITIALISED | AhrsEvents::ACCELEROMETER_IGNORED));
+        assert_eq!(monitor.last(AhrsEvents::MAGNETOMETER_IGNORED), Some(0.0));
+        let initialised = observed[0].unwrap();
+        assert!((initialised.time - 3.0).abs() < 0.05);
+        assert_eq!(initialised.duration, initialised.time);
+        assert_eq!(observed[1].unwrap().time, 4.0);
+        assert_eq!(monitor.last(AhrsEvents::ACCELEROMETER_IGNORED), Some(4.0));
+        assert_eq!(monitor.last(AhrsEvents::ACCELEROMETER_ACCEPTED), None);
+    }
+
+    #[test]
+    fn events_iterate_in_bit_order() {
+        let events = AhrsEvents::MAGNETOMETER_IGNORED | AhrsEvents::INITIALISED;
+
+        // Act
+        let mut iter = events.iter();
+
+        assert_eq!(iter.next(), Some(AhrsEvents::INITIALISED));
+        assert_eq!(iter.next(), Some(AhrsEvents::MAGNETOMETER_IGNORED));
+        assert_eq!(iter.next(), None);
+    }
+}
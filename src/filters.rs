@@ -0,0 +1,393 @@
+use crate::{Ahrs, Quaternion, Vector};
+
+/// Madgwick's gradient descent AHRS algorithm.
+///
+/// It uses the North-West-Up convention and has no initialisation, recovery,
+/// or rejection logic.
+#[derive(Debug, Clone, Copy)]
+pub struct Madgwick {
+    /// Gradient descent step size in radians per second.
+    pub beta: f32,
+    quaternion: Quaternion,
+    accelerometer: Vector,
+}
+
+impl Madgwick {
+    /// Create a new `Madgwick` instance.
+    ///
+    /// Arguments:
+    /// - `beta`: Gradient descent step size in radians per second.
+    pub fn new(beta: f32) -> Self {
+        Self {
+            beta,
+            quaternion: Quaternion::IDENTITY,
+            accelerometer: Vector::ZERO,
+        }
+    }
+
+    /// Returns the gradient of the error between the reference direction
+    /// rotated into the sensor frame and the measured direction.
+    fn gradient(quaternion: Quaternion, reference: Vector, measurement: Vector) -> Quaternion {
+        let reference = pure(reference);
+        let error = quaternion.conjugate() * reference * quaternion + pure(-measurement);
+        reference * quaternion * error * -2.0
+    }
+
+    fn step(&mut self, gyroscope: Vector, gradient: Quaternion, delta_time: f32) {
+        let q = self.quaternion;
+        let mut rate = q * pure(radians(gyroscope)) * 0.5;
+        let magnitude = magnitude(gradient);
+        if magnitude > 0.0 {
+            rate = rate + gradient * (-self.beta / magnitude);
+        }
+        self.quaternion = (q + rate * delta_time).normalise();
+    }
+}
+
+impl Default for Madgwick {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Ahrs for Madgwick {
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    ) {
+        if accelerometer.is_zero() || magnetometer.is_zero() {
+            return self.update_no_magnetometer(gyroscope, accelerometer, delta_time);
+        }
+        self.accelerometer = accelerometer;
+        let q = self.quaternion;
+        let field = q.rotate(magnetometer.normalise());
+        let reference = Vector::new(
+            libm::sqrtf(field.x * field.x + field.y * field.y),
+            0.0,
+            field.z,
+        );
+        let gradient = Self::gradient(q, UP, accelerometer.normalise())
+            + Self::gradient(q, reference, magnetometer.normalise());
+        self.step(gyroscope, gradient, delta_time);
+    }
+
+    fn update_no_magnetometer(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        delta_time: f32,
+    ) {
+        self.accelerometer = accelerometer;
+        let gradient = if accelerometer.is_zero() {
+            Quaternion::new(0.0, 0.0, 0.0, 0.0)
+        } else {
+            Self::gradient(self.quaternion, UP, accelerometer.normalise())
+        };
+        self.step(gyroscope, gradient, delta_time);
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    fn linear_acceleration(&self) -> Vector {
+        linear_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn earth_acceleration(&self) -> Vector {
+        earth_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.beta);
+    }
+}
+
+/// Mahony's nonlinear complementary filter with proportional and integral
+/// feedback, using the North-West-Up convention.
+#[derive(Debug, Clone, Copy)]
+pub struct Mahony {
+    /// Proportional gain.
+    pub kp: f32,
+    /// Integral gain.
+    pub ki: f32,
+    quaternion: Quaternion,
+    integral: Vector,
+    accelerometer: Vector,
+}
+
+impl Mahony {
+    /// Create a new `Mahony` instance.
+    ///
+    /// Arguments:
+    /// - `kp`: Proportional gain.
+    /// - `ki`: Integral gain.
+    pub fn new(kp: f32, ki: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            quaternion: Quaternion::IDENTITY,
+            integral: Vector::ZERO,
+            accelerometer: Vector::ZERO,
+        }
+    }
+
+    fn step(&mut self, gyroscope: Vector, error: Vector, delta_time: f32) {
+        let mut rate = radians(gyroscope);
+        if self.ki > 0.0 {
+            self.integral += error * (self.ki * delta_time);
+            rate += self.integral;
+        }
+        rate += error * self.kp;
+        let q = self.quaternion;
+        self.quaternion = (q + q * pure(rate) * (0.5 * delta_time)).normalise();
+    }
+}
+
+impl Default for Mahony {
+    fn default() -> Self {
+        Self::new(0.5, 0.0)
+    }
+}
+
+impl Ahrs for Mahony {
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    ) {
+        if accelerometer.is_zero() || magnetometer.is_zero() {
+            return self.update_no_magnetometer(gyroscope, accelerometer, delta_time);
+        }
+        self.accelerometer = accelerometer;
+        let matrix = self.quaternion.to_matrix();
+        let magnetometer = magnetometer.normalise();
+        let field = matrix * magnetometer;
+        let reference = Vector::new(
+            libm::sqrtf(field.x * field.x + field.y * field.y),
+            0.0,
+            field.z,
+        );
+        let error = accelerometer.normalise().cross(matrix.rows()[2])
+            + magnetometer.cross(matrix.transpose() * reference);
+        self.step(gyroscope, error, delta_time);
+    }
+
+    fn update_no_magnetometer(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        delta_time: f32,
+    ) {
+        self.accelerometer = accelerometer;
+        let error = if accelerometer.is_zero() {
+            Vector::ZERO
+        } else {
+            let gravity = self.quaternion.to_matrix().rows()[2];
+            accelerometer.normalise().cross(gravity)
+        };
+        self.step(gyroscope, error, delta_time);
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    fn linear_acceleration(&self) -> Vector {
+        linear_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn earth_acceleration(&self) -> Vector {
+        earth_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.kp, self.ki);
+    }
+}
+
+/// Complementary filter that integrates the gyroscope and corrects the
+/// result towards the accelerometer tilt and magnetometer heading with a
+/// first-order low-pass response, using the North-West-Up convention.
+#[derive(Debug, Clone, Copy)]
+pub struct Complementary {
+    /// Time constant of the correction in seconds.
+    pub time_constant: f32,
+    quaternion: Quaternion,
+    accelerometer: Vector,
+}
+
+impl Complementary {
+    /// Create a new `Complementary` instance.
+    ///
+    /// Arguments:
+    /// - `time_constant`: Time constant of the correction in seconds.
+    pub fn new(time_constant: f32) -> Self {
+        Self {
+            time_constant,
+            quaternion: Quaternion::IDENTITY,
+            accelerometer: Vector::ZERO,
+        }
+    }
+
+    fn integrate(&mut self, gyroscope: Vector, accelerometer: Vector, delta_time: f32) -> f32 {
+        self.accelerometer = accelerometer;
+        let q = self.quaternion;
+        self.quaternion = (q + q * pure(radians(gyroscope)) * (0.5 * delta_time)).normalise();
+        if accelerometer.is_zero() {
+            return 0.0;
+        }
+        let gain = delta_time / (self.time_constant + delta_time);
+
+        // Rotate the measured gravity direction towards vertical in the Earth
+        // frame
+        let up = self.quaternion.rotate(accelerometer.normalise());
+        let axis = up.cross(UP);
+        let angle = libm::atan2f(axis.magnitude(), up.z).to_degrees();
+        if !axis.is_zero() {
+            self.rotate_earth(axis, gain * angle);
+        }
+        gain
+    }
+
+    fn rotate_earth(&mut self, axis: Vector, angle: f32) {
+        let correction = Quaternion::from_axis_angle(axis, angle);
+        self.quaternion = (correction * self.quaternion).normalise();
+    }
+}
+
+impl Default for Complementary {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Ahrs for Complementary {
+    fn update(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Vector,
+        delta_time: f32,
+    ) {
+        let gain = self.integrate(gyroscope, accelerometer, delta_time);
+        if gain == 0.0 || magnetometer.is_zero() {
+            return;
+        }
+
+        // Rotate about vertical so that the horizontal field points north
+        let field = self.quaternion.rotate(magnetometer);
+        let heading = libm::atan2f(field.y, field.x).to_degrees();
+        self.rotate_earth(UP, -gain * heading);
+    }
+
+    fn update_no_magnetometer(
+        &mut self,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        delta_time: f32,
+    ) {
+        self.integrate(gyroscope, accelerometer, delta_time);
+    }
+
+    fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    fn linear_acceleration(&self) -> Vector {
+        linear_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn earth_acceleration(&self) -> Vector {
+        earth_acceleration(self.quaternion, self.accelerometer)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.time_constant);
+    }
+}
+
+const UP: Vector = Vector::new(0.0, 0.0, 1.0);
+
+fn pure(vector: Vector) -> Quaternion {
+    Quaternion::new(0.0, vector.x, vector.y, vector.z)
+}
+
+fn radians(vector: Vector) -> Vector {
+    vector * core::f32::consts::PI / 180.0
+}
+
+fn magnitude(q: Quaternion) -> f32 {
+    libm::sqrtf(q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z)
+}
+
+fn linear_acceleration(quaternion: Quaternion, accelerometer: Vector) -> Vector {
+    accelerometer - quaternion.to_matrix().rows()[2]
+}
+
+fn earth_acceleration(quaternion: Quaternion, accelerometer: Vector) -> Vector {
+    quaternion.rotate(accelerometer) - UP
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Segment, SimulationConfig, Simulator};
+
+    fn final_error(ahrs: &mut impl Ahrs) -> f32 {
+        let config = SimulationConfig {
+            initial_quaternion: Quaternion::from_axis_angle(Vector::new(1.0, 1.0, 0.0), 20.0),
+            ..Default::default()
+        };
+        let trajectory = [
+            Segment::stationary(15.0),
+            Segment::rotation(2.0, Vector::new(0.0, 0.0, 45.0)),
+            Segment::rotation(2.0, Vector::new(30.0, 0.0, 0.0)),
+            Segment::stationary(1.0),
+        ];
+        let mut last = None;
+        for sample in Simulator::new(config, &trajectory) {
+            ahrs.update(
+                sample.gyroscope,
+                sample.accelerometer,
+                sample.magnetometer,
+                0.01,
+            );
+            last = Some(sample);
+        }
+        ahrs.quaternion().angle_to(last.unwrap().quaternion)
+    }
+
+    #[test]
+    fn madgwick_converges_to_truth() {
+        // Act
+        let error = final_error(&mut Madgwick::default());
+
+        assert!(error < 1.0, "{error}");
+    }
+
+    #[test]
+    fn mahony_converges_to_truth() {
+        // Act
+        let error = final_error(&mut Mahony::new(1.0, 0.1));
+
+        assert!(error < 1.0, "{error}");
+    }
+
+    #[test]
+    fn complementary_converges_to_truth() {
+        let mut ahrs = Complementary::default();
+
+        // Act
+        let error = final_error(&mut ahrs);
+
+        assert!(error < 1.0, "{error}");
+        assert!(ahrs.linear_acceleration().magnitude() < 0.02);
+        assert!(ahrs.earth_acceleration().magnitude() < 0.02);
+    }
+}
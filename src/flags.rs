@@ -1,3 +1,5 @@
+use core::mem::MaybeUninit;
+
 use fusion_imu_sys as sys;
 
 /// AHRS algorithm flags.
@@ -34,3 +36,11 @@ impl Flags {
         self.inner.magneticRecovery
     }
 }
+
+impl Default for Flags {
+    fn default() -> Self {
+        // All fields are booleans or floats, for which zero is valid
+        let inner = unsafe { MaybeUninit::<sys::FusionAhrsFlags>::zeroed().assume_init() };
+        Self { inner }
+    }
+}
@@ -0,0 +1,252 @@
+use core::fmt;
+
+use crate::{Axis, InertialCalibration, Matrix, Vector};
+
+/// Gyroscope sensitivity and misalignment calibration from known rotations.
+///
+/// The calibration procedure is:
+/// 1. Hold the sensor still and pass the measurements to
+///    [`add_static_sample`](Self::add_static_sample) to estimate the offset.
+/// 2. For each axis, call [`start_rotation`](Self::start_rotation) with the
+///    axis and the known rotation angle (for example 360 degrees on a
+///    turntable), pass the measurements made during the rotation to
+///    [`add_rotation_sample`](Self::add_rotation_sample), and call
+///    [`finish_rotation`](Self::finish_rotation) once the sensor is still
+///    again.
+/// 3. Call [`calibrate`](Self::calibrate) to solve for the calibration.
+///
+/// Each rotation must be about a single fixed axis but doesn't need to be at
+/// a constant rate. The resulting misalignment matrix has unit-length
+/// columns so that the sensitivity holds the scale of each sensor axis.
+#[derive(Debug, Clone, Default)]
+pub struct GyroscopeCalibrator {
+    static_sum: [f64; 3],
+    static_count: u32,
+    current: Option<(Axis, Rotation)>,
+    rotations: [Option<Rotation>; 3],
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Rotation {
+    angle: f32,
+    integral: [f64; 3],
+    duration: f64,
+}
+
+impl GyroscopeCalibrator {
+    /// Create a new `GyroscopeCalibrator` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a gyroscope measurement in degrees per second made while the
+    /// sensor is stationary.
+    pub fn add_static_sample(&mut self, gyroscope: Vector) {
+        self.static_sum[0] += gyroscope.x as f64;
+        self.static_sum[1] += gyroscope.y as f64;
+        self.static_sum[2] += gyroscope.z as f64;
+        self.static_count += 1;
+    }
+
+    /// Starts a rotation about `axis` by `angle` degrees. The angle is
+    /// positive for a right-handed rotation.
+    ///
+    /// Any unfinished rotation is discarded.
+    pub fn start_rotation(&mut self, axis: Axis, angle: f32) {
+        self.current = Some((
+            axis,
+            Rotation {
+                angle,
+                ..Default::default()
+            },
+        ));
+    }
+
+    /// Adds a gyroscope measurement in degrees per second made during the
+    /// current rotation. Measurements are ignored if no rotation was started.
+    ///
+    /// Arguments:
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `delta_time`: Delta time in seconds.
+    pub fn add_rotation_sample(&mut self, gyroscope: Vector, delta_time: f32) {
+        if let Some((_, rotation)) = &mut self.current {
+            let delta_time = delta_time as f64;
+            rotation.integral[0] += gyroscope.x as f64 * delta_time;
+            rotation.integral[1] += gyroscope.y as f64 * delta_time;
+            rotation.integral[2] += gyroscope.z as f64 * delta_time;
+            rotation.duration += delta_time;
+        }
+    }
+
+    /// Finishes the current rotation. A previous rotation about the same axis
+    /// is replaced.
+    pub fn finish_rotation(&mut self) {
+        if let Some((axis, rotation)) = self.current.take() {
+            self.rotations[axis as usize] = Some(rotation);
+        }
+    }
+
+    /// Returns the gyroscope offset estimated from the static measurements.
+    pub fn offset(&self) -> Vector {
+        if self.static_count == 0 {
+            return Vector::ZERO;
+        }
+        let count = self.static_count as f64;
+        Vector::new(
+            (self.static_sum[0] / count) as f32,
+            (self.static_sum[1] / count) as f32,
+            (self.static_sum[2] / count) as f32,
+        )
+    }
+
+    /// Solves for the calibration that maps the integrated gyroscope
+    /// measurements onto the known rotations.
+    pub fn calibrate(&self) -> Result<InertialCalibration, GyroscopeCalibrationError> {
+        if self.current.is_some() {
+            return Err(GyroscopeCalibrationError::RotationInProgress);
+        }
+        let offset = self.offset();
+        let mut integrals = [Vector::ZERO; 3];
+        let mut angles = Vector::ZERO;
+        for (index, axis) in [Axis::X, Axis::Y, Axis::Z].into_iter().enumerate() {
+            let rotation = self.rotations[index]
+                .as_ref()
+                .ok_or(GyroscopeCalibrationError::MissingRotation(axis))?;
+            let duration = rotation.duration;
+            integrals[index] = Vector::new(
+                (rotation.integral[0] - offset.x as f64 * duration) as f32,
+                (rotation.integral[1] - offset.y as f64 * duration) as f32,
+                (rotation.integral[2] - offset.z as f64 * duration) as f32,
+            );
+            match axis {
+                Axis::X => angles.x = rotation.angle,
+                Axis::Y => angles.y = rotation.angle,
+                Axis::Z => angles.z = rotation.angle,
+            }
+        }
+
+        // The calibrated integral of each rotation must equal the known angle
+        // about its axis: A * [Ix Iy Iz] = diag(angles).
+        let integrals = Matrix::from_columns(integrals[0], integrals[1], integrals[2]);
+        let combined = Matrix::from_diagonal(angles)
+            * integrals
+                .inverse()
+                .ok_or(GyroscopeCalibrationError::Singular)?;
+
+        let [x, y, z] = combined.columns();
+        let sensitivity = Vector::new(x.magnitude(), y.magnitude(), z.magnitude());
+        if sensitivity.x == 0.0 || sensitivity.y == 0.0 || sensitivity.z == 0.0 {
+            return Err(GyroscopeCalibrationError::Singular);
+        }
+        let misalignment =
+            Matrix::from_columns(x / sensitivity.x, y / sensitivity.y, z / sensitivity.z);
+
+        Ok(InertialCalibration {
+            misalignment,
+            sensitivity,
+            offset,
+        })
+    }
+}
+
+/// Gyroscope calibration error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum GyroscopeCalibrationError {
+    /// No rotation about this axis was recorded.
+    MissingRotation(Axis),
+    /// A rotation was started but not finished.
+    RotationInProgress,
+    /// The recorded rotations don't span all three axes.
+    Singular,
+}
+
+impl fmt::Display for GyroscopeCalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRotation(axis) => write!(f, "missing rotation about the {axis:?} axis"),
+            Self::RotationInProgress => write!(f, "rotation in progress"),
+            Self::Singular => write!(f, "rotations don't span all three axes"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA_TIME: f32 = 0.01;
+
+    fn true_calibration() -> InertialCalibration {
+        InertialCalibration {
+            misalignment: Matrix::from_rows(
+                Vector::new(1.0, 0.02, -0.01),
+                Vector::new(-0.015, 1.0, 0.025),
+                Vector::new(0.01, -0.02, 1.0),
+            ),
+            sensitivity: Vector::new(1.02, 0.98, 1.03),
+            offset: Vector::new(0.5, -0.3, 0.2),
+        }
+    }
+
+    /// Returns the raw measurement that calibrates to `angular_rate`.
+    fn uncalibrate(calibration: &InertialCalibration, angular_rate: Vector) -> Vector {
+        let combined = calibration.misalignment * Matrix::from_diagonal(calibration.sensitivity);
+        combined.inverse().unwrap() * angular_rate + calibration.offset
+    }
+
+    fn simulate(calibrator: &mut GyroscopeCalibrator, calibration: &InertialCalibration) {
+        for _ in 0..500 {
+            calibrator.add_static_sample(uncalibrate(calibration, Vector::ZERO));
+        }
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            calibrator.start_rotation(axis, 360.0);
+            // Accelerate, rotate at a constant rate, and decelerate.
+            let rates = (0..100)
+                .map(|i| i as f32 * 1.8)
+                .chain((0..100).map(|_| 180.0))
+                .chain((0..100).map(|i| 180.0 - i as f32 * 1.8));
+            for rate in rates {
+                let gyroscope = uncalibrate(calibration, axis.unit_vector() * rate);
+                calibrator.add_rotation_sample(gyroscope, DELTA_TIME);
+            }
+            calibrator.finish_rotation();
+        }
+    }
+
+    #[test]
+    fn calibration_recovers_simulated_errors() {
+        let expected = true_calibration();
+        let mut calibrator = GyroscopeCalibrator::new();
+        simulate(&mut calibrator, &expected);
+
+        // Act
+        let calibration = calibrator.calibrate().unwrap();
+
+        let combined = calibration.misalignment * Matrix::from_diagonal(calibration.sensitivity);
+        let expected_combined = expected.misalignment * Matrix::from_diagonal(expected.sensitivity);
+        for (row, expected_row) in combined.rows().iter().zip(expected_combined.rows()) {
+            assert!((*row - expected_row).magnitude() < 1e-4);
+        }
+        assert!((calibration.offset - expected.offset).magnitude() < 1e-5);
+        let angular_rate = Vector::new(10.0, -20.0, 30.0);
+        let calibrated = calibration.apply(uncalibrate(&expected, angular_rate));
+        assert!((calibrated - angular_rate).magnitude() < 1e-2);
+    }
+
+    #[test]
+    fn calibration_requires_all_axes() {
+        let mut calibrator = GyroscopeCalibrator::new();
+        calibrator.start_rotation(Axis::X, 360.0);
+        calibrator.add_rotation_sample(Vector::new(360.0, 0.0, 0.0), 1.0);
+        calibrator.finish_rotation();
+
+        // Act
+        let result = calibrator.calibrate();
+
+        assert_eq!(
+            result,
+            Err(GyroscopeCalibrationError::MissingRotation(Axis::Y))
+        );
+    }
+}
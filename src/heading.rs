@@ -0,0 +1,160 @@
+use crate::Convention;
+
+/// Wraps an angle in degrees to the range [0, 360).
+pub fn wrap_360(angle: f32) -> f32 {
+    let wrapped = libm::fmodf(angle, 360.0);
+    let wrapped = if wrapped < 0.0 {
+        wrapped + 360.0
+    } else {
+        wrapped
+    };
+    if wrapped >= 360.0 {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// Wraps an angle in degrees to the range [-180, 180).
+pub fn wrap_180(angle: f32) -> f32 {
+    wrap_360(angle + 180.0) - 180.0
+}
+
+/// Returns the shortest signed rotation in degrees from one heading to
+/// another, in the range [-180, 180).
+pub fn heading_difference(from: f32, to: f32) -> f32 {
+    wrap_180(to - from)
+}
+
+/// Converts a magnetic heading to a true heading.
+///
+/// Arguments:
+/// - `heading`: Magnetic heading in degrees, clockwise from north.
+/// - `declination`: Magnetic declination in degrees, positive east.
+pub fn magnetic_to_true(heading: f32, declination: f32) -> f32 {
+    wrap_360(heading + declination)
+}
+
+/// Converts a true heading to a magnetic heading.
+///
+/// Arguments:
+/// - `heading`: True heading in degrees, clockwise from north.
+/// - `declination`: Magnetic declination in degrees, positive east.
+pub fn true_to_magnetic(heading: f32, declination: f32) -> f32 {
+    wrap_360(heading - declination)
+}
+
+/// Converts a yaw angle in degrees, as returned by
+/// [`Quaternion::to_euler`](crate::Quaternion::to_euler), to a heading in
+/// degrees clockwise from north in the range [0, 360).
+pub fn yaw_to_heading(yaw: f32, convention: Convention) -> f32 {
+    match convention {
+        Convention::NorthWestUp => wrap_360(-yaw),
+        Convention::EastNorthUp => wrap_360(90.0 - yaw),
+        Convention::NorthWestDown => wrap_360(yaw),
+    }
+}
+
+/// Converts a heading in degrees clockwise from north to a yaw angle in
+/// degrees in the range [-180, 180), as used by
+/// [`FusionAhrs::set_heading`](crate::FusionAhrs::set_heading) and
+/// [`FusionAhrs::update_external_heading`](crate::FusionAhrs::update_external_heading).
+pub fn heading_to_yaw(heading: f32, convention: Convention) -> f32 {
+    match convention {
+        Convention::NorthWestUp => wrap_180(-heading),
+        Convention::EastNorthUp => wrap_180(90.0 - heading),
+        Convention::NorthWestDown => wrap_180(heading),
+    }
+}
+
+/// Unwrapper of a wrapped angle that produces a continuous angle across
+/// turns, assuming that the angle changes by less than 180 degrees between
+/// updates.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct HeadingUnwrapper {
+    previous: Option<f32>,
+    continuous: f32,
+}
+
+impl HeadingUnwrapper {
+    /// Create a new `HeadingUnwrapper` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the unwrapper with a wrapped angle in degrees and returns the
+    /// continuous angle in degrees. The first angle is returned unchanged.
+    pub fn update(&mut self, angle: f32) -> f32 {
+        self.continuous = match self.previous {
+            Some(previous) => self.continuous + heading_difference(previous, angle),
+            None => angle,
+        };
+        self.previous = Some(angle);
+        self.continuous
+    }
+
+    /// Returns the most recent continuous angle in degrees.
+    pub fn continuous(&self) -> f32 {
+        self.continuous
+    }
+
+    /// Resets the unwrapper so that the next angle is returned unchanged.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FusionAhrs, Vector};
+
+    #[test]
+    fn headings_wrap_and_convert() {
+        // Act
+        let wrapped = [
+            wrap_360(-90.0),
+            wrap_360(720.0),
+            wrap_180(180.0),
+            wrap_180(-190.0),
+        ];
+
+        assert_eq!(wrapped, [270.0, 0.0, -180.0, 170.0]);
+        assert_eq!(heading_difference(350.0, 10.0), 20.0);
+        assert_eq!(heading_difference(10.0, 350.0), -20.0);
+        assert_eq!(magnetic_to_true(355.0, 10.0), 5.0);
+        assert_eq!(true_to_magnetic(5.0, 10.0), 355.0);
+        assert_eq!(yaw_to_heading(90.0, Convention::NorthWestUp), 270.0);
+        assert_eq!(yaw_to_heading(90.0, Convention::EastNorthUp), 0.0);
+        assert_eq!(heading_to_yaw(270.0, Convention::NorthWestDown), -90.0);
+    }
+
+    #[test]
+    fn continuous_yaw_follows_turns() {
+        let mut unwrapper = HeadingUnwrapper::new();
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_declination(10.0);
+        ahrs.set_continuous_yaw(true);
+        for _ in 0..300 {
+            ahrs.update_no_magnetometer(Vector::ZERO, Vector::new(0.0, 0.0, 1.0), 0.01);
+        }
+
+        // Act
+        let unwrapped = [170.0, -170.0, -10.0, 160.0].map(|yaw| unwrapper.update(yaw));
+        for _ in 0..500 {
+            ahrs.update_no_magnetometer(
+                Vector::new(0.0, 0.0, 90.0),
+                Vector::new(0.0, 0.0, 1.0),
+                0.01,
+            );
+        }
+
+        assert_eq!(unwrapped, [170.0, 190.0, 350.0, 520.0]);
+        ahrs.set_continuous_yaw(true);
+        assert!((ahrs.get_continuous_yaw().unwrap() - 450.0).abs() < 0.1);
+        assert!((ahrs.get_true_heading() - 280.0).abs() < 0.1);
+        assert_eq!(FusionAhrs::new().get_continuous_yaw(), None);
+    }
+}
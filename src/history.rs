@@ -0,0 +1,236 @@
+use crate::{
+    Ahrs, AhrsEvents, ByteSink, EventMonitor, LogRecord, LogWriter, SampleRecord, StateRecord,
+    Vector,
+};
+
+/// Measurements, outputs, flags, and internal states of one AHRS algorithm
+/// update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct HistoryEntry {
+    /// Measurements and outputs.
+    pub sample: SampleRecord,
+    /// Flags and internal states.
+    pub state: StateRecord,
+}
+
+/// Fixed-capacity ring buffer of the most recent AHRS algorithm updates for
+/// diagnostics.
+///
+/// With a trigger, recording stops a number of entries after one of the
+/// trigger events so that the window around the event is kept until
+/// [`rearm`](History::rearm) is called.
+pub struct History<const N: usize> {
+    entries: [Option<HistoryEntry>; N],
+    next: usize,
+    len: usize,
+    decimation: u32,
+    updates: u32,
+    monitor: EventMonitor,
+    trigger: AhrsEvents,
+    post_trigger: usize,
+    remaining: Option<usize>,
+    trigger_time: Option<u64>,
+}
+
+impl<const N: usize> History<N> {
+    /// Create a new `History` instance that records every update.
+    pub fn new() -> Self {
+        Self {
+            entries: [None; N],
+            next: 0,
+            len: 0,
+            decimation: 1,
+            updates: 0,
+            monitor: EventMonitor::new(),
+            trigger: AhrsEvents::NONE,
+            post_trigger: 0,
+            remaining: None,
+            trigger_time: None,
+        }
+    }
+
+    /// Records only every `decimation` updates. Trigger events are always
+    /// recorded.
+    pub fn with_decimation(mut self, decimation: u32) -> Self {
+        self.decimation = decimation.max(1);
+        self
+    }
+
+    /// Freezes the history `post_trigger` entries after any of the trigger
+    /// events.
+    pub fn with_trigger(mut self, events: AhrsEvents, post_trigger: usize) -> Self {
+        self.trigger = events;
+        self.post_trigger = post_trigger.min(N.saturating_sub(1));
+        self
+    }
+
+    /// Records an update of the AHRS algorithm. Does nothing while frozen.
+    ///
+    /// Arguments:
+    /// - `time`: Time in microseconds.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `magnetometer`: Magnetometer measurement in arbitrary units, if
+    ///   available.
+    /// - `ahrs`: AHRS algorithm after the update.
+    pub fn record(
+        &mut self,
+        time: u64,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Option<Vector>,
+        ahrs: &(impl Ahrs + ?Sized),
+    ) {
+        if self.is_frozen() {
+            return;
+        }
+        let events = self.monitor.update(ahrs, time as f32 * 1e-6);
+        let triggered = self.remaining.is_none() && events.intersects(self.trigger);
+        let decimated = !self.updates.is_multiple_of(self.decimation);
+        self.updates = self.updates.wrapping_add(1);
+        if decimated && !triggered {
+            return;
+        }
+
+        self.push(HistoryEntry {
+            sample: SampleRecord {
+                time,
+                gyroscope,
+                accelerometer,
+                magnetometer,
+                quaternion: ahrs.quaternion(),
+                linear_acceleration: ahrs.linear_acceleration(),
+            },
+            state: StateRecord::from_ahrs(time, ahrs),
+        });
+        if triggered {
+            self.trigger_time = Some(time);
+            self.remaining = Some(self.post_trigger);
+        } else if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+
+    /// Returns true if the history has been frozen by a trigger.
+    pub fn is_frozen(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Returns the time in microseconds of the trigger event, if triggered.
+    pub fn trigger_time(&self) -> Option<u64> {
+        self.trigger_time
+    }
+
+    /// Clears the history and waits for the next trigger event.
+    pub fn rearm(&mut self) {
+        self.clear();
+        self.remaining = None;
+        self.trigger_time = None;
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+        self.next = 0;
+        self.len = 0;
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the entries from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        let start = (self.next + N - self.len) % N.max(1);
+        (0..self.len).filter_map(move |index| self.entries[(start + index) % N].as_ref())
+    }
+
+    /// Writes the entries from the oldest to the newest as sample and state
+    /// records, for example to a serial port.
+    pub fn dump<S: ByteSink>(&self, writer: &mut LogWriter<S>) -> Result<(), S::Error> {
+        for entry in self.iter() {
+            writer.write_record(&LogRecord::Sample(entry.sample))?;
+            writer.write_record(&LogRecord::State(entry.state))?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        if N == 0 {
+            return;
+        }
+        self.entries[self.next] = Some(entry);
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FusionAhrs, LogHeader, Settings, SliceSink};
+
+    #[test]
+    fn decimated_entries_wrap_around() {
+        let ahrs = FusionAhrs::new();
+        let mut history = History::<4>::new().with_decimation(2);
+
+        // Act
+        for index in 0..10 {
+            history.record(index, Vector::ZERO, Vector::ZERO, None, &ahrs);
+        }
+
+        let times: [u64; 4] =
+            core::array::from_fn(|index| history.iter().nth(index).unwrap().sample.time);
+        assert_eq!(times, [2, 4, 6, 8]);
+        assert!(!history.is_frozen());
+        let mut buffer = [0; 1024];
+        let mut writer =
+            LogWriter::new(SliceSink::new(&mut buffer), &LogHeader::default()).unwrap();
+        history.dump(&mut writer).unwrap();
+        assert_eq!(writer.into_inner().written().len(), 208 + 4 * (78 + 30));
+    }
+
+    #[test]
+    fn trigger_freezes_window_around_event() {
+        let mut settings = Settings::new();
+        settings.set_acceleration_rejection(10.0);
+        settings.set_recovery_trigger_period(1000);
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(settings);
+        let mut history = History::<5>::new().with_trigger(AhrsEvents::ACCELEROMETER_IGNORED, 2);
+
+        // Act
+        for index in 0..600 {
+            let accelerometer = if index < 400 {
+                Vector::new(0.0, 0.0, 1.0)
+            } else {
+                Vector::new(1.0, 0.0, 1.0)
+            };
+            ahrs.update_no_magnetometer(Vector::ZERO, accelerometer, 0.01);
+            history.record(index * 10_000, Vector::ZERO, accelerometer, None, &ahrs);
+        }
+
+        assert!(history.is_frozen());
+        assert_eq!(history.trigger_time(), Some(4_000_000));
+        let first = history.iter().next().unwrap();
+        assert_eq!(first.sample.time, 3_980_000);
+        assert!(!first.state.accelerometer_ignored);
+        assert!(history.iter().nth(2).unwrap().state.accelerometer_ignored);
+        assert_eq!(history.iter().last().unwrap().sample.time, 4_020_000);
+    }
+}
@@ -1,3 +1,5 @@
+use core::mem::MaybeUninit;
+
 use fusion_imu_sys as sys;
 
 /// AHRS algorithm flags.
@@ -48,3 +50,11 @@ impl InternalStates {
         self.inner.magneticRecoveryTrigger
     }
 }
+
+impl Default for InternalStates {
+    fn default() -> Self {
+        // All fields are booleans or floats, for which zero is valid
+        let inner = unsafe { MaybeUninit::<sys::FusionAhrsInternalStates>::zeroed().assume_init() };
+        Self { inner }
+    }
+}
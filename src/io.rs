@@ -0,0 +1,58 @@
+//! Reading and writing recorded sensor data.
+
+pub mod csv;
+
+use crate::{SampleRecord, Vector};
+
+/// Timestamped sensor measurements.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample {
+    /// Time in seconds. Double precision so that the differences between
+    /// samples remain exact in long recordings.
+    pub time: f64,
+    /// Gyroscope measurement in degrees per second.
+    pub gyroscope: Vector,
+    /// Accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Magnetometer measurement in arbitrary units, if available.
+    pub magnetometer: Option<Vector>,
+    /// Heading measurement in degrees, if available.
+    pub heading: Option<f32>,
+}
+
+impl From<SampleRecord> for Sample {
+    fn from(record: SampleRecord) -> Self {
+        Self {
+            time: record.time as f64 * 1e-6,
+            gyroscope: record.gyroscope,
+            accelerometer: record.accelerometer,
+            magnetometer: record.magnetometer,
+            heading: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Quaternion;
+
+    #[test]
+    fn long_recording_keeps_microsecond_resolution() {
+        let record = |time| SampleRecord {
+            time,
+            gyroscope: Vector::ZERO,
+            accelerometer: Vector::ZERO,
+            magnetometer: None,
+            quaternion: Quaternion::IDENTITY,
+            linear_acceleration: Vector::ZERO,
+        };
+        let hours = 10 * 3_600_000_000;
+
+        // Act
+        let [previous, next] = [hours, hours + 1_000].map(|time| Sample::from(record(time)));
+
+        assert!(((next.time - previous.time) as f32 - 0.001).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,532 @@
+//! CSV files in the format of the x-io example data.
+//!
+//! The input columns are `Time (s)`, `Gyroscope X/Y/Z (deg/s)`,
+//! `Accelerometer X/Y/Z (g)`, and optionally `Magnetometer X/Y/Z (uT)` and
+//! `Heading (deg)`.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::io::Sample;
+use crate::{Ahrs, Vector};
+
+/// Header of the files written by [`CsvWriter`].
+///
+/// | Columns | Content                                                        |
+/// |---------|----------------------------------------------------------------|
+/// | 1       | Time in seconds                                                |
+/// | 2-5     | Quaternion W, X, Y, Z                                          |
+/// | 6-8     | Euler roll, pitch, yaw in degrees                              |
+/// | 9-11    | Linear acceleration X, Y, Z in g                               |
+/// | 12-14   | Earth acceleration X, Y, Z in g                                |
+/// | 15-18   | Flags: initialising, angular rate, acceleration, and magnetic recovery (0 or 1) |
+/// | 19-21   | Acceleration error in degrees, accelerometer ignored (0 or 1), acceleration recovery trigger |
+/// | 22-24   | Magnetic error in degrees, magnetometer ignored (0 or 1), magnetic recovery trigger |
+pub const OUTPUT_HEADER: &str = "Time (s),\
+Quaternion W,Quaternion X,Quaternion Y,Quaternion Z,\
+Roll (deg),Pitch (deg),Yaw (deg),\
+Linear Acceleration X (g),Linear Acceleration Y (g),Linear Acceleration Z (g),\
+Earth Acceleration X (g),Earth Acceleration Y (g),Earth Acceleration Z (g),\
+Initialising,Angular Rate Recovery,Acceleration Recovery,Magnetic Recovery,\
+Acceleration Error (deg),Accelerometer Ignored,Acceleration Recovery Trigger,\
+Magnetic Error (deg),Magnetometer Ignored,Magnetic Recovery Trigger";
+
+/// Error reading a CSV file.
+#[derive(Debug)]
+pub struct CsvError {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Kind of error.
+    pub kind: CsvErrorKind,
+}
+
+/// Kind of [`CsvError`].
+#[derive(Debug)]
+pub enum CsvErrorKind {
+    /// Reading failed.
+    Io(io::Error),
+    /// The file is empty.
+    Empty,
+    /// A required column is missing from the header.
+    MissingColumn(&'static str),
+    /// A row has too few columns.
+    ColumnCount {
+        /// Number of columns required.
+        expected: usize,
+        /// Number of columns found.
+        found: usize,
+    },
+    /// A value is not a number.
+    InvalidNumber {
+        /// Column number, starting at 1.
+        column: usize,
+        /// Text of the value.
+        value: String,
+    },
+    /// A time is not after the time of the previous row.
+    NonIncreasingTime {
+        /// Time of the previous row in seconds.
+        previous: f64,
+        /// Time of the row in seconds.
+        time: f64,
+    },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            CsvErrorKind::Io(error) => write!(f, "{error}"),
+            CsvErrorKind::Empty => write!(f, "file is empty"),
+            CsvErrorKind::MissingColumn(name) => write!(f, "missing column \"{name}\""),
+            CsvErrorKind::ColumnCount { expected, found } => {
+                write!(f, "expected {expected} columns, found {found}")
+            }
+            CsvErrorKind::InvalidNumber { column, value } => {
+                write!(f, "column {column}: invalid number \"{value}\"")
+            }
+            CsvErrorKind::NonIncreasingTime { previous, time } => {
+                write!(f, "time {time} is not after the previous time {previous}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            CsvErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Column indices of the input fields.
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    time: usize,
+    gyroscope: [usize; 3],
+    accelerometer: [usize; 3],
+    magnetometer: Option<[usize; 3]>,
+    heading: Option<usize>,
+}
+
+impl Layout {
+    /// Layout of files without a header.
+    fn positional(columns: usize) -> Self {
+        Self {
+            time: 0,
+            gyroscope: [1, 2, 3],
+            accelerometer: [4, 5, 6],
+            magnetometer: (columns >= 10).then_some([7, 8, 9]),
+            heading: (columns >= 11).then_some(10),
+        }
+    }
+
+    /// Layout from the column names of a header.
+    fn from_header(names: &[&str]) -> Result<Self, CsvErrorKind> {
+        let find = |field: &'static str, aliases: &[&str]| {
+            names
+                .iter()
+                .position(|name| {
+                    let name = normalise(name);
+                    aliases.iter().any(|alias| name == *alias)
+                })
+                .ok_or(CsvErrorKind::MissingColumn(field))
+        };
+        let find_axes = |field: &'static str, aliases: &[&str]| -> Result<[usize; 3], _> {
+            let mut indices = [0; 3];
+            for (index, axis) in indices.iter_mut().zip(["x", "y", "z"]) {
+                let names: Vec<String> = aliases
+                    .iter()
+                    .flat_map(|alias| {
+                        [
+                            std::format!("{alias} {axis}"),
+                            std::format!("{alias}{axis}"),
+                        ]
+                    })
+                    .collect();
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                *index = find(field, &names)?;
+            }
+            Ok(indices)
+        };
+
+        Ok(Self {
+            time: find("Time", &["time", "timestamp", "t"])?,
+            gyroscope: find_axes("Gyroscope", &["gyroscope", "gyro", "g"])?,
+            accelerometer: find_axes("Accelerometer", &["accelerometer", "accel", "acc", "a"])?,
+            magnetometer: find_axes("Magnetometer", &["magnetometer", "mag", "m"]).ok(),
+            heading: find("Heading", &["heading"]).ok(),
+        })
+    }
+
+    /// Number of columns a row must have.
+    fn required_columns(&self) -> usize {
+        let axes = self.gyroscope.iter().chain(&self.accelerometer);
+        axes.copied().fold(self.time, usize::max) + 1
+    }
+}
+
+/// Returns the column name in lower case without units.
+fn normalise(name: &str) -> String {
+    let name = match name.find('(') {
+        Some(index) => &name[..index],
+        None => name,
+    };
+    name.trim().trim_matches('"').trim().to_lowercase()
+}
+
+/// Reader of sensor samples from a CSV file.
+///
+/// The columns are identified by name if the file has a header. Column names
+/// are case-insensitive, may include units in brackets, and may be
+/// abbreviated, e.g. `gyro x` or `gx`. Without a header, the columns must be
+/// in the x-io order. Magnetometer and heading columns are optional, and rows
+/// with empty magnetometer or heading values have no such measurement. The
+/// time of each row must be after the time of the previous row.
+pub struct CsvReader<R> {
+    lines: io::Lines<R>,
+    line: usize,
+    layout: Layout,
+    first: Option<String>,
+    previous_time: Option<f64>,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Create a new `CsvReader` instance. The header, if present, is read
+    /// immediately.
+    pub fn new(reader: R) -> Result<Self, CsvError> {
+        let mut lines = reader.lines();
+        let mut line = 0;
+        let first = loop {
+            line += 1;
+            match lines.next() {
+                Some(Ok(text)) if text.trim().is_empty() => continue,
+                Some(Ok(text)) => break text,
+                Some(Err(error)) => {
+                    return Err(CsvError {
+                        line,
+                        kind: CsvErrorKind::Io(error),
+                    })
+                }
+                None => {
+                    return Err(CsvError {
+                        line,
+                        kind: CsvErrorKind::Empty,
+                    })
+                }
+            }
+        };
+
+        let fields: Vec<&str> = first.split(',').collect();
+        let is_header = fields
+            .iter()
+            .any(|field| field.trim().parse::<f32>().is_err());
+        if is_header {
+            let layout = Layout::from_header(&fields).map_err(|kind| CsvError { line, kind })?;
+            Ok(Self {
+                lines,
+                line,
+                layout,
+                first: None,
+                previous_time: None,
+            })
+        } else {
+            Ok(Self {
+                lines,
+                line: line - 1,
+                layout: Layout::positional(fields.len()),
+                first: Some(first),
+                previous_time: None,
+            })
+        }
+    }
+
+    /// Returns true if the file has magnetometer columns.
+    pub fn has_magnetometer(&self) -> bool {
+        self.layout.magnetometer.is_some()
+    }
+
+    /// Returns true if the file has a heading column.
+    pub fn has_heading(&self) -> bool {
+        self.layout.heading.is_some()
+    }
+
+    fn parse(&mut self, text: &str) -> Result<Sample, CsvErrorKind> {
+        let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+        let expected = self.layout.required_columns();
+        if fields.len() < expected {
+            return Err(CsvErrorKind::ColumnCount {
+                expected,
+                found: fields.len(),
+            });
+        }
+        let invalid = |column: usize| CsvErrorKind::InvalidNumber {
+            column: column + 1,
+            value: fields[column].into(),
+        };
+        let number = |column: usize| fields[column].parse::<f32>().map_err(|_| invalid(column));
+        let vector = |columns: [usize; 3]| -> Result<Vector, CsvErrorKind> {
+            Ok(Vector::new(
+                number(columns[0])?,
+                number(columns[1])?,
+                number(columns[2])?,
+            ))
+        };
+
+        let magnetometer = match self.layout.magnetometer {
+            Some(columns)
+                if columns
+                    .iter()
+                    .all(|&column| fields.get(column).is_some_and(|field| !field.is_empty())) =>
+            {
+                Some(vector(columns)?)
+            }
+            _ => None,
+        };
+        let heading = match self.layout.heading {
+            Some(column) if fields.get(column).is_some_and(|field| !field.is_empty()) => {
+                Some(number(column)?)
+            }
+            _ => None,
+        };
+        let time = fields[self.layout.time]
+            .parse::<f64>()
+            .ok()
+            .filter(|time| time.is_finite())
+            .ok_or_else(|| invalid(self.layout.time))?;
+        if let Some(previous) = self.previous_time.filter(|&previous| time <= previous) {
+            return Err(CsvErrorKind::NonIncreasingTime { previous, time });
+        }
+        let sample = Sample {
+            time,
+            gyroscope: vector(self.layout.gyroscope)?,
+            accelerometer: vector(self.layout.accelerometer)?,
+            magnetometer,
+            heading,
+        };
+        self.previous_time = Some(time);
+        Ok(sample)
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<Sample, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let text = match self.first.take() {
+                Some(text) => text,
+                None => match self.lines.next()? {
+                    Ok(text) => text,
+                    Err(error) => {
+                        return Some(Err(CsvError {
+                            line: self.line,
+                            kind: CsvErrorKind::Io(error),
+                        }))
+                    }
+                },
+            };
+            if text.trim().is_empty() {
+                continue;
+            }
+            return Some(self.parse(&text).map_err(|kind| CsvError {
+                line: self.line,
+                kind,
+            }));
+        }
+    }
+}
+
+/// Writer of AHRS algorithm outputs to a CSV file with the columns of
+/// [`OUTPUT_HEADER`].
+pub struct CsvWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Create a new `CsvWriter` instance and write the header.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{OUTPUT_HEADER}")?;
+        Ok(Self { writer })
+    }
+
+    /// Writes the current outputs of the AHRS algorithm.
+    ///
+    /// Arguments:
+    /// - `time`: Time in seconds.
+    /// - `ahrs`: AHRS algorithm.
+    pub fn write(&mut self, time: f64, ahrs: &(impl Ahrs + ?Sized)) -> io::Result<()> {
+        let quaternion = ahrs.quaternion();
+        let euler = quaternion.to_euler();
+        let linear = ahrs.linear_acceleration();
+        let earth = ahrs.earth_acceleration();
+        let flags = ahrs.flags();
+        let states = ahrs.internal_states();
+        let bit = |value: bool| value as u8;
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            time,
+            quaternion.w,
+            quaternion.x,
+            quaternion.y,
+            quaternion.z,
+            euler.roll,
+            euler.pitch,
+            euler.yaw,
+            linear.x,
+            linear.y,
+            linear.z,
+            earth.x,
+            earth.y,
+            earth.z,
+            bit(flags.initialising()),
+            bit(flags.angular_rate_recovery()),
+            bit(flags.acceleration_recovery()),
+            bit(flags.magnetic_recovery()),
+            states.acceleration_error(),
+            bit(states.accelerometer_ignored()),
+            states.acceleration_recovery_trigger(),
+            states.magnetic_error(),
+            bit(states.magnetometer_ignored()),
+            states.magnetic_recovery_trigger(),
+        )
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FusionAhrs;
+
+    #[test]
+    fn x_io_format_is_read() {
+        let data = "Time (s),Gyroscope X (deg/s),Gyroscope Y (deg/s),Gyroscope Z (deg/s),\
+Accelerometer X (g),Accelerometer Y (g),Accelerometer Z (g),\
+Magnetometer X (uT),Magnetometer Y (uT),Magnetometer Z (uT)
+0.01,1,2,3,0,0,1,10,20,30
+0.02,1,2,3,0,0,1,,,
+";
+
+        // Act
+        let samples: Vec<_> = CsvReader::new(data.as_bytes())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].gyroscope, Vector::new(1.0, 2.0, 3.0));
+        assert_eq!(samples[0].magnetometer, Some(Vector::new(10.0, 20.0, 30.0)));
+        assert_eq!(samples[1].time, 0.02);
+        assert_eq!(samples[1].magnetometer, None);
+    }
+
+    #[test]
+    fn reordered_header_without_magnetometer_is_read() {
+        let data = "ax,ay,az,gx,gy,gz,timestamp,Heading (deg)\n0,0,1,4,5,6,0.5,90\n";
+
+        // Act
+        let mut reader = CsvReader::new(data.as_bytes()).unwrap();
+
+        assert!(!reader.has_magnetometer());
+        assert!(reader.has_heading());
+        let sample = reader.next().unwrap().unwrap();
+        assert_eq!(sample.time, 0.5);
+        assert_eq!(sample.gyroscope, Vector::new(4.0, 5.0, 6.0));
+        assert_eq!(sample.accelerometer, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(sample.heading, Some(90.0));
+    }
+
+    #[test]
+    fn non_increasing_time_is_rejected() {
+        let data = "0.01,1,2,3,0,0,1\n0.02,1,2,3,0,0,1\n0.02,1,2,3,0,0,1\n0.03,1,2,3,0,0,1\n";
+        let mut reader = CsvReader::new(data.as_bytes()).unwrap();
+
+        // Act
+        let results: Vec<_> = reader.by_ref().collect();
+
+        assert!(results[1].is_ok());
+        let error = results[2].as_ref().unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(matches!(
+            error.kind,
+            CsvErrorKind::NonIncreasingTime {
+                previous: 0.02,
+                time: 0.02
+            }
+        ));
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn non_finite_time_is_rejected() {
+        let data = "0.01,1,2,3,0,0,1\nNaN,1,2,3,0,0,1\ninf,1,2,3,0,0,1\n0.005,1,2,3,0,0,1\n";
+        let reader = CsvReader::new(data.as_bytes()).unwrap();
+
+        // Act
+        let results: Vec<_> = reader.collect();
+
+        for result in &results[1..3] {
+            assert!(matches!(
+                result.as_ref().unwrap_err().kind,
+                CsvErrorKind::InvalidNumber { column: 1, .. }
+            ));
+        }
+        assert!(matches!(
+            results[3].as_ref().unwrap_err().kind,
+            CsvErrorKind::NonIncreasingTime { .. }
+        ));
+    }
+
+    #[test]
+    fn errors_include_line_number() {
+        let data = "0,1,2,3,0,0,1\n\n0.01,1,x,3,0,0,1\n0.02,1,2\n";
+        let mut reader = CsvReader::new(data.as_bytes()).unwrap();
+
+        // Act
+        let first = reader.next().unwrap();
+        let second = reader.next().unwrap().unwrap_err();
+        let third = reader.next().unwrap().unwrap_err();
+
+        assert!(first.is_ok());
+        assert_eq!(second.line, 3);
+        assert!(matches!(
+            second.kind,
+            CsvErrorKind::InvalidNumber { column: 3, .. }
+        ));
+        assert_eq!(third.line, 4);
+        assert!(matches!(
+            third.kind,
+            CsvErrorKind::ColumnCount {
+                expected: 7,
+                found: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn output_has_header_columns() {
+        let mut writer = CsvWriter::new(Vec::new()).unwrap();
+
+        // Act
+        writer.write(0.01, &FusionAhrs::new()).unwrap();
+
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let mut lines = output.lines();
+        let header = lines.next().unwrap().split(',').count();
+        let row = lines.next().unwrap().split(',').count();
+        assert_eq!(header, 24);
+        assert_eq!(row, header);
+    }
+}
@@ -2,18 +2,75 @@
 #![no_std]
 #![warn(missing_docs)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 mod ahrs;
+mod alignment;
+#[cfg(feature = "std")]
+mod allan;
+mod axes;
+mod benchmark;
 mod calibration;
+mod calibration_report;
+mod confidence;
+mod driver;
+mod eskf;
+mod events;
+mod filters;
 mod flags;
+mod gyroscope_calibrator;
+mod heading;
+mod history;
 mod internal_states;
+#[cfg(feature = "std")]
+pub mod io;
+mod log;
+mod magnetic_calibrator;
 mod math;
+#[cfg(feature = "mavlink")]
+pub mod mavlink;
 mod offset;
+mod pipeline;
+#[cfg(feature = "std")]
+pub mod plot;
+#[cfg(feature = "ros")]
+pub mod ros;
+mod scaling;
 mod settings;
+mod simulation;
+mod temperature;
+mod tilt;
+#[cfg(feature = "std")]
+mod tuner;
 
 pub use ahrs::*;
+pub use alignment::*;
+#[cfg(feature = "std")]
+pub use allan::*;
+pub use axes::*;
+pub use benchmark::*;
 pub use calibration::*;
+pub use calibration_report::*;
+pub use confidence::*;
+pub use driver::*;
+pub use eskf::*;
+pub use events::*;
+pub use filters::*;
 pub use flags::*;
+pub use gyroscope_calibrator::*;
+pub use heading::*;
+pub use history::*;
 pub use internal_states::*;
+pub use log::*;
+pub use magnetic_calibrator::*;
 pub use math::*;
 pub use offset::*;
+pub use pipeline::*;
+pub use scaling::*;
 pub use settings::*;
+pub use simulation::*;
+pub use temperature::*;
+pub use tilt::*;
+#[cfg(feature = "std")]
+pub use tuner::*;
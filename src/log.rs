@@ -0,0 +1,1015 @@
+//! Binary log format.
+//!
+//! A log starts with a header followed by records. All values are little
+//! endian.
+//!
+//! Header (208 bytes):
+//!
+//! | Offset | Size | Content                                              |
+//! |--------|------|------------------------------------------------------|
+//! | 0      | 4    | Magic `FIMU`                                         |
+//! | 4      | 2    | Version, currently 1                                 |
+//! | 6      | 4    | Sample rate in Hz                                    |
+//! | 10     | 4    | State record interval in samples, 0 for none         |
+//! | 14     | 1    | Gyroscope offset correction (0 or 1)                 |
+//! | 15     | 1    | Earth axes convention                                |
+//! | 16     | 20   | Gain, gyroscope range, acceleration rejection, magnetic rejection (f32), recovery trigger period (u32) |
+//! | 36     | 60   | Gyroscope misalignment, sensitivity, and offset      |
+//! | 96     | 60   | Accelerometer misalignment, sensitivity, and offset  |
+//! | 156    | 48   | Magnetometer soft-iron matrix and hard-iron offset   |
+//! | 204    | 4    | CRC-32 of bytes 0 to 203                             |
+//!
+//! Each record starts with a type byte and ends with the CRC-32 of the type
+//! and payload. Times are in microseconds.
+//!
+//! Sample record (type 1, 78 bytes): time (u64), magnetometer present (u8),
+//! gyroscope, accelerometer, and magnetometer (3 × f32 each), quaternion
+//! (4 × f32), and linear acceleration (3 × f32).
+//!
+//! State record (type 2, 30 bytes): time (u64), flag bits (u8), acceleration
+//! error, acceleration recovery trigger, magnetic error, and magnetic recovery
+//! trigger (f32). Flag bits from the least significant: initialising, angular
+//! rate recovery, acceleration recovery, magnetic recovery, accelerometer
+//! ignored, magnetometer ignored.
+
+use crate::{Ahrs, InertialCalibration, MagneticCalibration, Matrix, Quaternion, Settings, Vector};
+
+/// Log format version written by [`LogWriter`].
+pub const LOG_VERSION: u16 = 1;
+
+const MAGIC: [u8; 4] = *b"FIMU";
+const HEADER_SIZE: usize = 208;
+const SAMPLE_RECORD: u8 = 1;
+const SAMPLE_RECORD_SIZE: usize = 78;
+const STATE_RECORD: u8 = 2;
+const STATE_RECORD_SIZE: usize = 30;
+
+/// Destination of log bytes.
+pub trait ByteSink {
+    /// Error returned when writing fails.
+    type Error;
+
+    /// Writes all bytes.
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Byte sink writing into a buffer.
+#[derive(Debug)]
+pub struct SliceSink<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Create a new `SliceSink` instance.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// Returns the bytes written.
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.position]
+    }
+}
+
+/// Error returned when a [`SliceSink`] buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BufferFull;
+
+impl ByteSink for SliceSink<'_> {
+    type Error = BufferFull;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let end = self.position + bytes.len();
+        let destination = self.buffer.get_mut(self.position..end).ok_or(BufferFull)?;
+        destination.copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+}
+
+/// Byte sink writing to a [`std::io::Write`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSink<W>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> ByteSink for IoSink<W> {
+    type Error = std::io::Error;
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write_all(bytes)
+    }
+}
+
+/// Configuration recorded in the log header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogHeader {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Number of samples between state records, or 0 for no state records.
+    pub state_interval: u32,
+    /// Whether the gyroscope offset is corrected at runtime.
+    pub offset_correction: bool,
+    /// AHRS algorithm settings.
+    pub settings: Settings,
+    /// Gyroscope calibration applied to the logged measurements.
+    pub gyroscope_calibration: InertialCalibration,
+    /// Accelerometer calibration applied to the logged measurements.
+    pub accelerometer_calibration: InertialCalibration,
+    /// Magnetometer calibration applied to the logged measurements.
+    pub magnetometer_calibration: MagneticCalibration,
+}
+
+impl Default for LogHeader {
+    fn default() -> Self {
+        Self {
+            sample_rate: 100,
+            state_interval: 100,
+            offset_correction: true,
+            settings: Settings::default(),
+            gyroscope_calibration: InertialCalibration::default(),
+            accelerometer_calibration: InertialCalibration::default(),
+            magnetometer_calibration: MagneticCalibration::default(),
+        }
+    }
+}
+
+impl LogHeader {
+    fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut encoder = Encoder::<HEADER_SIZE>::new();
+        let settings = &self.settings;
+        encoder.bytes(&MAGIC);
+        encoder.u16(LOG_VERSION);
+        encoder.u32(self.sample_rate);
+        encoder.u32(self.state_interval);
+        encoder.u8(self.offset_correction as u8);
+        encoder.u8(settings.convention() as u8);
+        encoder.f32(settings.gain());
+        encoder.f32(settings.gyroscope_range());
+        encoder.f32(settings.acceleration_rejection());
+        encoder.f32(settings.magnetic_rejection());
+        encoder.u32(settings.recovery_trigger_period());
+        for calibration in [self.gyroscope_calibration, self.accelerometer_calibration] {
+            encoder.matrix(calibration.misalignment);
+            encoder.vector(calibration.sensitivity);
+            encoder.vector(calibration.offset);
+        }
+        encoder.matrix(self.magnetometer_calibration.soft_iron);
+        encoder.vector(self.magnetometer_calibration.hard_iron);
+        encoder.crc()
+    }
+}
+
+/// Sensor measurements and AHRS algorithm outputs of one sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SampleRecord {
+    /// Time in microseconds.
+    pub time: u64,
+    /// Calibrated gyroscope measurement in degrees per second, before the
+    /// runtime offset correction.
+    pub gyroscope: Vector,
+    /// Calibrated accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Calibrated magnetometer measurement in arbitrary units, if available.
+    pub magnetometer: Option<Vector>,
+    /// Quaternion describing the sensor relative to the Earth.
+    pub quaternion: Quaternion,
+    /// Linear acceleration in g.
+    pub linear_acceleration: Vector,
+}
+
+impl SampleRecord {
+    fn encode(&self) -> [u8; SAMPLE_RECORD_SIZE] {
+        let mut encoder = Encoder::<SAMPLE_RECORD_SIZE>::new();
+        encoder.u8(SAMPLE_RECORD);
+        encoder.u64(self.time);
+        encoder.u8(self.magnetometer.is_some() as u8);
+        encoder.vector(self.gyroscope);
+        encoder.vector(self.accelerometer);
+        encoder.vector(self.magnetometer.unwrap_or_default());
+        let quaternion = self.quaternion;
+        for value in [quaternion.w, quaternion.x, quaternion.y, quaternion.z] {
+            encoder.f32(value);
+        }
+        encoder.vector(self.linear_acceleration);
+        encoder.crc()
+    }
+}
+
+/// AHRS algorithm flags and internal states.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct StateRecord {
+    /// Time in microseconds.
+    pub time: u64,
+    /// Initialising flag.
+    pub initialising: bool,
+    /// Angular rate recovery flag.
+    pub angular_rate_recovery: bool,
+    /// Acceleration recovery flag.
+    pub acceleration_recovery: bool,
+    /// Magnetic recovery flag.
+    pub magnetic_recovery: bool,
+    /// Acceleration error in degrees.
+    pub acceleration_error: f32,
+    /// Accelerometer ignored.
+    pub accelerometer_ignored: bool,
+    /// Acceleration recovery trigger.
+    pub acceleration_recovery_trigger: f32,
+    /// Magnetic error in degrees.
+    pub magnetic_error: f32,
+    /// Magnetometer ignored.
+    pub magnetometer_ignored: bool,
+    /// Magnetic recovery trigger.
+    pub magnetic_recovery_trigger: f32,
+}
+
+impl StateRecord {
+    /// Create a new `StateRecord` instance from the current flags and
+    /// internal states of the AHRS algorithm.
+    pub fn from_ahrs(time: u64, ahrs: &(impl Ahrs + ?Sized)) -> Self {
+        let flags = ahrs.flags();
+        let states = ahrs.internal_states();
+        Self {
+            time,
+            initialising: flags.initialising(),
+            angular_rate_recovery: flags.angular_rate_recovery(),
+            acceleration_recovery: flags.acceleration_recovery(),
+            magnetic_recovery: flags.magnetic_recovery(),
+            acceleration_error: states.acceleration_error(),
+            accelerometer_ignored: states.accelerometer_ignored(),
+            acceleration_recovery_trigger: states.acceleration_recovery_trigger(),
+            magnetic_error: states.magnetic_error(),
+            magnetometer_ignored: states.magnetometer_ignored(),
+            magnetic_recovery_trigger: states.magnetic_recovery_trigger(),
+        }
+    }
+
+    fn encode(&self) -> [u8; STATE_RECORD_SIZE] {
+        let mut encoder = Encoder::<STATE_RECORD_SIZE>::new();
+        let bits = [
+            self.initialising,
+            self.angular_rate_recovery,
+            self.acceleration_recovery,
+            self.magnetic_recovery,
+            self.accelerometer_ignored,
+            self.magnetometer_ignored,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (index, &bit)| bits | (bit as u8) << index);
+        encoder.u8(STATE_RECORD);
+        encoder.u64(self.time);
+        encoder.u8(bits);
+        encoder.f32(self.acceleration_error);
+        encoder.f32(self.acceleration_recovery_trigger);
+        encoder.f32(self.magnetic_error);
+        encoder.f32(self.magnetic_recovery_trigger);
+        encoder.crc()
+    }
+}
+
+/// Log record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum LogRecord {
+    /// Sensor measurements and AHRS algorithm outputs.
+    Sample(SampleRecord),
+    /// AHRS algorithm flags and internal states.
+    State(StateRecord),
+}
+
+/// Writer of binary logs.
+pub struct LogWriter<S> {
+    sink: S,
+    state_interval: u32,
+    samples: u64,
+}
+
+impl<S: ByteSink> LogWriter<S> {
+    /// Create a new `LogWriter` instance and write the header.
+    pub fn new(mut sink: S, header: &LogHeader) -> Result<Self, S::Error> {
+        sink.write_all(&header.encode())?;
+        Ok(Self {
+            sink,
+            state_interval: header.state_interval,
+            samples: 0,
+        })
+    }
+
+    /// Writes a record.
+    pub fn write_record(&mut self, record: &LogRecord) -> Result<(), S::Error> {
+        match record {
+            LogRecord::Sample(sample) => self.sink.write_all(&sample.encode()),
+            LogRecord::State(state) => self.sink.write_all(&state.encode()),
+        }
+    }
+
+    /// Writes a sample record with the outputs of the AHRS algorithm after it
+    /// has been updated with the measurements. A state record follows every
+    /// state interval samples.
+    ///
+    /// Arguments:
+    /// - `time`: Time in microseconds.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `magnetometer`: Magnetometer measurement in arbitrary units, if
+    ///   available.
+    /// - `ahrs`: AHRS algorithm.
+    pub fn log(
+        &mut self,
+        time: u64,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Option<Vector>,
+        ahrs: &(impl Ahrs + ?Sized),
+    ) -> Result<(), S::Error> {
+        self.write_record(&LogRecord::Sample(SampleRecord {
+            time,
+            gyroscope,
+            accelerometer,
+            magnetometer,
+            quaternion: ahrs.quaternion(),
+            linear_acceleration: ahrs.linear_acceleration(),
+        }))?;
+        self.samples += 1;
+        if self.state_interval > 0 && self.samples.is_multiple_of(u64::from(self.state_interval)) {
+            self.write_record(&LogRecord::State(StateRecord::from_ahrs(time, ahrs)))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the byte sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+/// Little-endian encoder into a fixed-size buffer with a trailing CRC-32.
+struct Encoder<const N: usize> {
+    buffer: [u8; N],
+    position: usize,
+}
+
+impl<const N: usize> Encoder<N> {
+    fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn vector(&mut self, vector: Vector) {
+        for value in [vector.x, vector.y, vector.z] {
+            self.f32(value);
+        }
+    }
+
+    fn matrix(&mut self, matrix: Matrix) {
+        for row in matrix.rows() {
+            self.vector(row);
+        }
+    }
+
+    /// Appends the CRC and returns the buffer.
+    fn crc(mut self) -> [u8; N] {
+        let crc = crc32(&self.buffer[..self.position]);
+        self.u32(crc);
+        debug_assert_eq!(self.position, N);
+        self.buffer
+    }
+}
+
+/// CRC-32 (IEEE 802.3) lookup table.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(feature = "std")]
+pub use reader::*;
+
+#[cfg(feature = "std")]
+mod reader {
+    use std::fmt;
+    use std::io::{self, Read};
+
+    use super::*;
+    use crate::{Convention, FusionAhrs, FusionOffset};
+
+    /// Error reading a binary log.
+    #[derive(Debug)]
+    pub enum LogError {
+        /// Reading failed.
+        Io(io::Error),
+        /// The data is not a binary log.
+        InvalidMagic,
+        /// The log version is not supported.
+        UnsupportedVersion(u16),
+        /// The header CRC does not match.
+        HeaderCrc,
+        /// A record CRC does not match.
+        RecordCrc {
+            /// Byte offset of the record.
+            offset: u64,
+        },
+        /// A record has an unknown type.
+        UnknownRecord {
+            /// Record type.
+            kind: u8,
+            /// Byte offset of the record.
+            offset: u64,
+        },
+        /// The log ends in the middle of a record.
+        Truncated {
+            /// Byte offset of the record.
+            offset: u64,
+        },
+        /// A sample time is not after the time of the previous sample, for
+        /// example after the device restarted its clock.
+        NonIncreasingTime {
+            /// Time in microseconds of the previous sample.
+            previous: u64,
+            /// Time in microseconds of the sample.
+            time: u64,
+        },
+    }
+
+    impl fmt::Display for LogError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Io(error) => write!(f, "{error}"),
+                Self::InvalidMagic => write!(f, "not a binary log"),
+                Self::UnsupportedVersion(version) => {
+                    write!(f, "unsupported log version {version}")
+                }
+                Self::HeaderCrc => write!(f, "header CRC mismatch"),
+                Self::RecordCrc { offset } => write!(f, "record CRC mismatch at byte {offset}"),
+                Self::UnknownRecord { kind, offset } => {
+                    write!(f, "unknown record type {kind} at byte {offset}")
+                }
+                Self::Truncated { offset } => write!(f, "truncated record at byte {offset}"),
+                Self::NonIncreasingTime { previous, time } => {
+                    write!(
+                        f,
+                        "time {time} us is not after the previous time {previous} us"
+                    )
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for LogError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Io(error) => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    /// Reader of binary logs.
+    ///
+    /// Iterating yields the records in order. Iteration stops after the
+    /// first error.
+    pub struct LogReader<R> {
+        reader: R,
+        header: LogHeader,
+        offset: u64,
+        failed: bool,
+    }
+
+    impl<R: Read> LogReader<R> {
+        /// Create a new `LogReader` instance and read the header.
+        pub fn new(mut reader: R) -> Result<Self, LogError> {
+            let mut bytes = [0; HEADER_SIZE];
+            reader
+                .read_exact(&mut bytes)
+                .map_err(|error| match error.kind() {
+                    io::ErrorKind::UnexpectedEof => LogError::Truncated { offset: 0 },
+                    _ => LogError::Io(error),
+                })?;
+            let header = LogHeader::decode(&bytes).map_err(|error| match error {
+                DecodeError::InvalidMagic => LogError::InvalidMagic,
+                DecodeError::UnsupportedVersion(version) => LogError::UnsupportedVersion(version),
+                DecodeError::Crc => LogError::HeaderCrc,
+            })?;
+            Ok(Self {
+                reader,
+                header,
+                offset: HEADER_SIZE as u64,
+                failed: false,
+            })
+        }
+
+        /// Returns the log header.
+        pub fn header(&self) -> &LogHeader {
+            &self.header
+        }
+
+        /// Returns an iterator over the sample records only.
+        pub fn samples(self) -> impl Iterator<Item = Result<SampleRecord, LogError>> {
+            self.filter_map(|record| match record {
+                Ok(LogRecord::Sample(sample)) => Some(Ok(sample)),
+                Ok(LogRecord::State(_)) => None,
+                Err(error) => Some(Err(error)),
+            })
+        }
+
+        /// Replays the logged measurements through a new AHRS algorithm with
+        /// the logged settings, including the gyroscope offset correction if
+        /// it was enabled. The observer is called after each update. Returns
+        /// the number of samples, or an error if the time of a sample is not
+        /// after the time of the previous sample.
+        pub fn replay(
+            self,
+            mut observer: impl FnMut(&SampleRecord, &FusionAhrs),
+        ) -> Result<usize, LogError> {
+            let header = self.header;
+            let mut ahrs = FusionAhrs::new();
+            ahrs.set_settings(header.settings);
+            let mut offset = FusionOffset::new(header.sample_rate);
+            let mut previous_time = None;
+            let mut count = 0;
+
+            for sample in self.samples() {
+                let sample = sample?;
+                let delta_time = match previous_time {
+                    Some(previous) => match sample.time.checked_sub(previous) {
+                        Some(delta) if delta > 0 => delta as f32 * 1e-6,
+                        _ => {
+                            return Err(LogError::NonIncreasingTime {
+                                previous,
+                                time: sample.time,
+                            })
+                        }
+                    },
+                    None => 1.0 / header.sample_rate as f32,
+                };
+                previous_time = Some(sample.time);
+                let gyroscope = if header.offset_correction {
+                    offset.update(sample.gyroscope)
+                } else {
+                    sample.gyroscope
+                };
+                match sample.magnetometer {
+                    Some(magnetometer) => {
+                        ahrs.update(gyroscope, sample.accelerometer, magnetometer, delta_time)
+                    }
+                    None => {
+                        ahrs.update_no_magnetometer(gyroscope, sample.accelerometer, delta_time)
+                    }
+                }
+                observer(&sample, &ahrs);
+                count += 1;
+            }
+            Ok(count)
+        }
+
+        fn read_record(&mut self) -> Result<Option<LogRecord>, LogError> {
+            let offset = self.offset;
+            let mut buffer = [0; SAMPLE_RECORD_SIZE];
+            match self.reader.read(&mut buffer[..1]) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {
+                    return self.read_record()
+                }
+                Err(error) => return Err(LogError::Io(error)),
+            }
+            let size = match buffer[0] {
+                SAMPLE_RECORD => SAMPLE_RECORD_SIZE,
+                STATE_RECORD => STATE_RECORD_SIZE,
+                kind => return Err(LogError::UnknownRecord { kind, offset }),
+            };
+            self.reader
+                .read_exact(&mut buffer[1..size])
+                .map_err(|error| match error.kind() {
+                    io::ErrorKind::UnexpectedEof => LogError::Truncated { offset },
+                    _ => LogError::Io(error),
+                })?;
+            self.offset += size as u64;
+            let record = &buffer[..size];
+            if !crc_matches(record) {
+                return Err(LogError::RecordCrc { offset });
+            }
+            let payload = &record[1..size - 4];
+            Ok(Some(match buffer[0] {
+                SAMPLE_RECORD => LogRecord::Sample(SampleRecord::decode(payload)),
+                _ => LogRecord::State(StateRecord::decode(payload)),
+            }))
+        }
+    }
+
+    impl<R: Read> Iterator for LogReader<R> {
+        type Item = Result<LogRecord, LogError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.failed {
+                return None;
+            }
+            let result = self.read_record();
+            self.failed = result.is_err();
+            result.transpose()
+        }
+    }
+
+    impl LogHeader {
+        fn decode(bytes: &[u8; HEADER_SIZE]) -> Result<Self, DecodeError> {
+            let mut decoder = Decoder::new(&bytes[4..]);
+            if bytes[..4] != MAGIC {
+                return Err(DecodeError::InvalidMagic);
+            }
+            let version = decoder.u16();
+            if version != LOG_VERSION {
+                return Err(DecodeError::UnsupportedVersion(version));
+            }
+            if !crc_matches(bytes) {
+                return Err(DecodeError::Crc);
+            }
+            let sample_rate = decoder.u32();
+            let state_interval = decoder.u32();
+            let offset_correction = decoder.u8() != 0;
+            let mut settings = Settings::new();
+            settings.set_convention(match decoder.u8() {
+                1 => Convention::EastNorthUp,
+                2 => Convention::NorthWestDown,
+                _ => Convention::NorthWestUp,
+            });
+            settings.set_gain(decoder.f32());
+            settings.set_gyroscope_range(decoder.f32());
+            settings.set_acceleration_rejection(decoder.f32());
+            settings.set_magnetic_rejection(decoder.f32());
+            settings.set_recovery_trigger_period(decoder.u32());
+            let mut inertial = || InertialCalibration {
+                misalignment: decoder.matrix(),
+                sensitivity: decoder.vector(),
+                offset: decoder.vector(),
+            };
+            let gyroscope_calibration = inertial();
+            let accelerometer_calibration = inertial();
+            let magnetometer_calibration = MagneticCalibration {
+                soft_iron: decoder.matrix(),
+                hard_iron: decoder.vector(),
+            };
+            Ok(Self {
+                sample_rate,
+                state_interval,
+                offset_correction,
+                settings,
+                gyroscope_calibration,
+                accelerometer_calibration,
+                magnetometer_calibration,
+            })
+        }
+    }
+
+    impl SampleRecord {
+        fn decode(payload: &[u8]) -> Self {
+            let mut decoder = Decoder::new(payload);
+            let time = decoder.u64();
+            let has_magnetometer = decoder.u8() != 0;
+            let gyroscope = decoder.vector();
+            let accelerometer = decoder.vector();
+            let magnetometer = decoder.vector();
+            Self {
+                time,
+                gyroscope,
+                accelerometer,
+                magnetometer: has_magnetometer.then_some(magnetometer),
+                quaternion: Quaternion::new(
+                    decoder.f32(),
+                    decoder.f32(),
+                    decoder.f32(),
+                    decoder.f32(),
+                ),
+                linear_acceleration: decoder.vector(),
+            }
+        }
+    }
+
+    impl StateRecord {
+        fn decode(payload: &[u8]) -> Self {
+            let mut decoder = Decoder::new(payload);
+            let time = decoder.u64();
+            let bits = decoder.u8();
+            let bit = |index: u8| bits & (1 << index) != 0;
+            Self {
+                time,
+                initialising: bit(0),
+                angular_rate_recovery: bit(1),
+                acceleration_recovery: bit(2),
+                magnetic_recovery: bit(3),
+                accelerometer_ignored: bit(4),
+                magnetometer_ignored: bit(5),
+                acceleration_error: decoder.f32(),
+                acceleration_recovery_trigger: decoder.f32(),
+                magnetic_error: decoder.f32(),
+                magnetic_recovery_trigger: decoder.f32(),
+            }
+        }
+    }
+
+    /// Reasons a log fails to decode.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum DecodeError {
+        InvalidMagic,
+        UnsupportedVersion(u16),
+        Crc,
+    }
+
+    /// Little-endian decoder of validated bytes.
+    struct Decoder<'a> {
+        bytes: &'a [u8],
+    }
+
+    impl<'a> Decoder<'a> {
+        fn new(bytes: &'a [u8]) -> Self {
+            Self { bytes }
+        }
+
+        fn take<const N: usize>(&mut self) -> [u8; N] {
+            let (first, rest) = self.bytes.split_at(N);
+            self.bytes = rest;
+            first.try_into().unwrap_or([0; N])
+        }
+
+        fn u8(&mut self) -> u8 {
+            self.take::<1>()[0]
+        }
+
+        fn u16(&mut self) -> u16 {
+            u16::from_le_bytes(self.take())
+        }
+
+        fn u32(&mut self) -> u32 {
+            u32::from_le_bytes(self.take())
+        }
+
+        fn u64(&mut self) -> u64 {
+            u64::from_le_bytes(self.take())
+        }
+
+        fn f32(&mut self) -> f32 {
+            f32::from_le_bytes(self.take())
+        }
+
+        fn vector(&mut self) -> Vector {
+            Vector::new(self.f32(), self.f32(), self.f32())
+        }
+
+        fn matrix(&mut self) -> Matrix {
+            Matrix::from_rows(self.vector(), self.vector(), self.vector())
+        }
+    }
+
+    /// Returns true if the last four bytes are the CRC of the preceding bytes.
+    fn crc_matches(bytes: &[u8]) -> bool {
+        let (data, crc) = bytes.split_at(bytes.len() - 4);
+        crc32(data).to_le_bytes() == crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Convention, FusionAhrs};
+
+    fn header() -> LogHeader {
+        let mut settings = Settings::new();
+        settings.set_convention(Convention::NorthWestDown);
+        settings.set_gain(0.3);
+        LogHeader {
+            sample_rate: 1000,
+            state_interval: 2,
+            settings,
+            gyroscope_calibration: InertialCalibration {
+                offset: Vector::new(0.1, -0.2, 0.3),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn crc_matches_reference() {
+        // Act
+        let crc = crc32(b"123456789");
+
+        assert_eq!(crc, 0xcbf43926);
+    }
+
+    #[test]
+    fn writer_fills_slice() {
+        let mut buffer = [0; HEADER_SIZE + SAMPLE_RECORD_SIZE + 10];
+        let mut writer = LogWriter::new(SliceSink::new(&mut buffer), &header()).unwrap();
+        let ahrs = FusionAhrs::new();
+
+        // Act
+        let first = writer.log(0, Vector::ZERO, Vector::ZERO, None, &ahrs);
+        let second = writer.log(1000, Vector::ZERO, Vector::ZERO, None, &ahrs);
+
+        assert_eq!(first, Ok(()));
+        assert_eq!(second, Err(BufferFull));
+        assert_eq!(
+            writer.into_inner().written().len(),
+            HEADER_SIZE + SAMPLE_RECORD_SIZE
+        );
+    }
+
+    #[cfg(feature = "std")]
+    fn write_log(samples: usize) -> std::vec::Vec<u8> {
+        let mut writer = LogWriter::new(IoSink(std::vec::Vec::new()), &header()).unwrap();
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(header().settings);
+        for index in 0..samples {
+            let gyroscope = Vector::new(index as f32, 0.0, 1.0);
+            let accelerometer = Vector::new(0.0, 0.0, -1.0);
+            let magnetometer = (index % 2 == 0).then_some(Vector::new(20.0, 0.0, 40.0));
+            ahrs.update_no_magnetometer(gyroscope, accelerometer, 0.001);
+            let time = index as u64 * 1000;
+            writer
+                .log(time, gyroscope, accelerometer, magnetometer, &ahrs)
+                .unwrap();
+        }
+        writer.into_inner().0
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn log_round_trips() {
+        let bytes = write_log(4);
+
+        // Act
+        let reader = LogReader::new(bytes.as_slice()).unwrap();
+
+        assert_eq!(*reader.header(), header());
+        let records: std::vec::Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 6);
+        let LogRecord::Sample(sample) = records[0] else {
+            panic!("expected sample record");
+        };
+        assert_eq!(sample.gyroscope, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(sample.magnetometer, Some(Vector::new(20.0, 0.0, 40.0)));
+        assert!(matches!(
+            records[2],
+            LogRecord::State(StateRecord { time: 1000, .. })
+        ));
+        let LogRecord::Sample(sample) = records[3] else {
+            panic!("expected sample record");
+        };
+        assert_eq!(sample.time, 2000);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn corrupt_record_is_rejected() {
+        let mut bytes = write_log(4);
+        bytes[HEADER_SIZE + SAMPLE_RECORD_SIZE + 10] ^= 0x01;
+
+        // Act
+        let records: std::vec::Vec<_> = LogReader::new(bytes.as_slice()).unwrap().collect();
+
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_ok());
+        assert!(matches!(
+            records[1],
+            Err(LogError::RecordCrc { offset }) if offset == (HEADER_SIZE + SAMPLE_RECORD_SIZE) as u64
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn corrupt_header_and_truncation_are_rejected() {
+        let mut bytes = write_log(1);
+        bytes.pop();
+        let mut corrupt = bytes.clone();
+        corrupt[20] ^= 0x01;
+
+        // Act
+        let header = LogReader::new(corrupt.as_slice());
+        let truncated = LogReader::new(bytes.as_slice()).unwrap().next();
+
+        assert!(matches!(header, Err(LogError::HeaderCrc)));
+        assert!(matches!(
+            truncated,
+            Some(Err(LogError::Truncated { offset })) if offset == HEADER_SIZE as u64
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn replay_matches_logged_quaternion() {
+        let mut header = header();
+        header.offset_correction = false;
+        let mut writer = LogWriter::new(IoSink(std::vec::Vec::new()), &header).unwrap();
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(header.settings);
+        for index in 0..100 {
+            let gyroscope = Vector::new(10.0, 0.0, 0.0);
+            let accelerometer = Vector::new(0.0, 0.0, -1.0);
+            let dt = 0.001;
+            ahrs.update_no_magnetometer(gyroscope, accelerometer, dt);
+            writer
+                .log(index * 1000 + 1000, gyroscope, accelerometer, None, &ahrs)
+                .unwrap();
+        }
+        let bytes = writer.into_inner().0;
+        let mut last = None;
+
+        // Act
+        let count = LogReader::new(bytes.as_slice())
+            .unwrap()
+            .replay(|sample, ahrs| last = Some((sample.quaternion, ahrs.get_quaternion())))
+            .unwrap();
+
+        assert_eq!(count, 100);
+        let (logged, replayed) = last.unwrap();
+        assert!(logged.angle_to(replayed) < 0.01);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn replay_rejects_out_of_order_time() {
+        let mut writer = LogWriter::new(IoSink(std::vec::Vec::new()), &header()).unwrap();
+        let ahrs = FusionAhrs::new();
+        let accelerometer = Vector::new(0.0, 0.0, -1.0);
+        for time in [1000, 2000, 500, 3000] {
+            writer
+                .log(time, Vector::ZERO, accelerometer, None, &ahrs)
+                .unwrap();
+        }
+        let bytes = writer.into_inner().0;
+        let mut count = 0;
+
+        // Act
+        let result = LogReader::new(bytes.as_slice())
+            .unwrap()
+            .replay(|_, _| count += 1);
+
+        assert_eq!(count, 2);
+        assert!(matches!(
+            result,
+            Err(LogError::NonIncreasingTime {
+                previous: 2000,
+                time: 500
+            })
+        ));
+    }
+}
@@ -0,0 +1,446 @@
+use crate::math::symmetric_eigen;
+use crate::{MagneticCalibration, Matrix, Vector};
+
+/// Number of direction bins used to measure the sample coverage.
+const BIN_COUNT: usize = 24;
+
+/// Online magnetometer calibration settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MagneticCalibratorSettings {
+    /// Estimate the soft-iron matrix in addition to the hard-iron offset.
+    pub soft_iron: bool,
+    /// Recursive least squares forgetting factor applied per accepted
+    /// sample. Values closer to 1 adapt more slowly.
+    pub forgetting_factor: f32,
+    /// Minimum distance between accepted samples as a fraction of the field
+    /// strength.
+    pub minimum_separation: f32,
+    /// Minimum number of accepted samples before a calibration is published.
+    pub minimum_samples: u32,
+    /// Confidence required to publish a calibration, between 0 and 1.
+    pub confidence_threshold: f32,
+    /// Relative field magnitude error at which the confidence drops to zero.
+    pub maximum_residual: f32,
+    /// Minimum number of accepted samples between published calibrations.
+    pub publish_interval: u32,
+}
+
+impl Default for MagneticCalibratorSettings {
+    fn default() -> Self {
+        Self {
+            soft_iron: false,
+            forgetting_factor: 0.998,
+            minimum_separation: 0.05,
+            minimum_samples: 50,
+            confidence_threshold: 0.7,
+            maximum_residual: 0.1,
+            publish_interval: 25,
+        }
+    }
+}
+
+/// Online magnetometer calibration quality metrics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MagneticCalibrationQuality {
+    /// Fraction of measurement directions recently covered, between 0 and 1.
+    pub coverage: f32,
+    /// RMS field magnitude error of the calibrated measurements relative to
+    /// the field strength.
+    pub residual: f32,
+    /// Number of accepted samples.
+    pub samples: u64,
+    /// Overall confidence, between 0 and 1.
+    pub confidence: f32,
+}
+
+/// Streaming magnetometer calibration.
+///
+/// Hard-iron (and optionally soft-iron) estimates are updated by recursive
+/// least squares as the device rotates. Samples that are too close to the
+/// previously accepted sample are rejected so that a stationary device
+/// doesn't dominate the fit. An updated [`MagneticCalibration`] is published
+/// only when the confidence passes
+/// [`confidence_threshold`](MagneticCalibratorSettings::confidence_threshold).
+///
+/// The calibrator doesn't allocate and can run alongside
+/// [`FusionAhrs::update`](crate::FusionAhrs::update).
+#[derive(Debug, Clone)]
+pub struct MagneticCalibrator {
+    settings: MagneticCalibratorSettings,
+    sphere: RecursiveLeastSquares<4>,
+    ellipsoid: RecursiveLeastSquares<9>,
+    estimate: MagneticCalibration,
+    field_strength: f32,
+    previous: Option<Vector>,
+    bins: [Option<u64>; BIN_COUNT],
+    samples: u64,
+    residual_squared: f32,
+    last_published: Option<u64>,
+}
+
+impl MagneticCalibrator {
+    /// Create a new `MagneticCalibrator` instance.
+    pub fn new(settings: MagneticCalibratorSettings) -> Self {
+        let forgetting_factor = settings.forgetting_factor as f64;
+        Self {
+            settings,
+            sphere: RecursiveLeastSquares::new(forgetting_factor),
+            ellipsoid: RecursiveLeastSquares::new(forgetting_factor),
+            estimate: MagneticCalibration::default(),
+            field_strength: 0.0,
+            previous: None,
+            bins: [None; BIN_COUNT],
+            samples: 0,
+            residual_squared: 0.0,
+            last_published: None,
+        }
+    }
+
+    /// Resets the calibrator while maintaining the current settings.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.settings);
+    }
+
+    /// Updates the calibration with an uncalibrated magnetometer measurement.
+    ///
+    /// Returns the updated calibration when it is published.
+    pub fn update(&mut self, magnetometer: Vector) -> Option<MagneticCalibration> {
+        if magnetometer.is_zero() || !magnetometer.magnitude().is_finite() {
+            return None;
+        }
+        if let Some(previous) = self.previous {
+            let separation = self.settings.minimum_separation * self.field_strength;
+            if (magnetometer - previous).magnitude() <= separation {
+                return None;
+            }
+        }
+        self.previous = Some(magnetometer);
+
+        // Track the error of the prior estimate before updating the fit
+        if self.field_strength > 0.0 {
+            let error = self.estimate.apply(magnetometer).magnitude() / self.field_strength - 1.0;
+            let alpha = 1.0 - self.settings.forgetting_factor;
+            self.residual_squared += alpha.max(0.01) * (error * error - self.residual_squared);
+        }
+
+        let direction = (magnetometer - self.estimate.hard_iron).normalise();
+        self.bins[bin(direction)] = Some(self.samples);
+        self.samples += 1;
+
+        self.update_sphere(magnetometer);
+        if self.settings.soft_iron {
+            self.update_ellipsoid(magnetometer);
+        }
+
+        let quality = self.quality();
+        let interval_elapsed = match self.last_published {
+            Some(last) => self.samples - last >= u64::from(self.settings.publish_interval),
+            None => true,
+        };
+        if quality.samples >= u64::from(self.settings.minimum_samples)
+            && quality.confidence >= self.settings.confidence_threshold
+            && interval_elapsed
+        {
+            self.last_published = Some(self.samples);
+            return Some(self.estimate);
+        }
+        None
+    }
+
+    /// Returns the current calibration estimate, whether or not it has been
+    /// published.
+    pub fn estimate(&self) -> MagneticCalibration {
+        self.estimate
+    }
+
+    /// Returns the estimated field strength in the magnetometer units.
+    pub fn field_strength(&self) -> f32 {
+        self.field_strength
+    }
+
+    /// Returns the calibration quality metrics.
+    pub fn quality(&self) -> MagneticCalibrationQuality {
+        // Bins are only counted while they are within the memory of the fit
+        let memory = (1.0 / (1.0 - self.settings.forgetting_factor).max(1e-6)) as u64;
+        let covered = self
+            .bins
+            .iter()
+            .flatten()
+            .filter(|&&sample| self.samples - sample <= memory)
+            .count();
+        let coverage = covered as f32 / BIN_COUNT as f32;
+        let residual = libm::sqrtf(self.residual_squared);
+        let confidence = coverage * (1.0 - residual / self.settings.maximum_residual).max(0.0);
+        MagneticCalibrationQuality {
+            coverage,
+            residual,
+            samples: self.samples,
+            confidence,
+        }
+    }
+
+    /// Fits |m|² = 2 b·m + c where b is the hard-iron offset.
+    fn update_sphere(&mut self, magnetometer: Vector) {
+        let (x, y, z) = (
+            magnetometer.x as f64,
+            magnetometer.y as f64,
+            magnetometer.z as f64,
+        );
+        self.sphere
+            .update([2.0 * x, 2.0 * y, 2.0 * z, 1.0], x * x + y * y + z * z);
+        if self.settings.soft_iron && self.ellipsoid_estimate().is_some() {
+            return;
+        }
+        let [bx, by, bz, c] = self.sphere.theta;
+        let radius_squared = c + bx * bx + by * by + bz * bz;
+        if radius_squared > 0.0 {
+            self.field_strength = libm::sqrt(radius_squared) as f32;
+            self.estimate = MagneticCalibration {
+                soft_iron: Matrix::IDENTITY,
+                hard_iron: Vector::new(bx as f32, by as f32, bz as f32),
+            };
+        }
+    }
+
+    /// Fits a general ellipsoid with the trace of the quadratic form
+    /// constrained to 3.
+    fn update_ellipsoid(&mut self, magnetometer: Vector) {
+        let (x, y, z) = (
+            magnetometer.x as f64,
+            magnetometer.y as f64,
+            magnetometer.z as f64,
+        );
+        self.ellipsoid.update(
+            [
+                x * x - z * z,
+                y * y - z * z,
+                -2.0 * x * y,
+                -2.0 * x * z,
+                -2.0 * y * z,
+                -2.0 * x,
+                -2.0 * y,
+                -2.0 * z,
+                -1.0,
+            ],
+            x * x + y * y + z * z,
+        );
+        if let Some((calibration, field_strength)) = self.ellipsoid_estimate() {
+            self.estimate = calibration;
+            self.field_strength = field_strength;
+        }
+    }
+
+    /// Converts the ellipsoid fit to a calibration, or returns `None` if the
+    /// fit isn't an ellipsoid yet.
+    fn ellipsoid_estimate(&self) -> Option<(MagneticCalibration, f32)> {
+        if self.samples < 9 {
+            return None;
+        }
+        let [alpha, beta, d, e, f, g, h, i, j] = self.ellipsoid.theta;
+        let a = Matrix::from_rows(
+            Vector::new((1.0 - alpha) as f32, d as f32, e as f32),
+            Vector::new(d as f32, (1.0 - beta) as f32, f as f32),
+            Vector::new(e as f32, f as f32, (1.0 + alpha + beta) as f32),
+        );
+        let linear = Vector::new(g as f32, h as f32, i as f32);
+        let hard_iron = -(a.inverse()? * linear);
+        let scale = hard_iron.dot(a * hard_iron) - j as f32;
+        if scale <= 0.0 {
+            return None;
+        }
+        let (values, vectors) = symmetric_eigen(a * (1.0 / scale));
+        if values.x <= 0.0 || values.y <= 0.0 || values.z <= 0.0 {
+            return None;
+        }
+        // Scale the soft-iron matrix to a unit determinant so that the
+        // calibrated field strength is the geometric mean of the radii.
+        let field_strength = libm::powf(values.x * values.y * values.z, -1.0 / 6.0);
+        let roots = Vector::new(
+            libm::sqrtf(values.x),
+            libm::sqrtf(values.y),
+            libm::sqrtf(values.z),
+        );
+        let soft_iron =
+            vectors * Matrix::from_diagonal(roots) * vectors.transpose() * field_strength;
+        Some((
+            MagneticCalibration {
+                soft_iron,
+                hard_iron,
+            },
+            field_strength,
+        ))
+    }
+}
+
+impl Default for MagneticCalibrator {
+    fn default() -> Self {
+        Self::new(MagneticCalibratorSettings::default())
+    }
+}
+
+/// Returns the direction bin: the dominant axis and its sign select a cube
+/// face, and the signs of the other two axes select a quadrant of that face.
+fn bin(direction: Vector) -> usize {
+    let (ax, ay, az) = (
+        libm::fabsf(direction.x),
+        libm::fabsf(direction.y),
+        libm::fabsf(direction.z),
+    );
+    let (face, u, v) = if ax >= ay && ax >= az {
+        (usize::from(direction.x < 0.0), direction.y, direction.z)
+    } else if ay >= az {
+        (2 + usize::from(direction.y < 0.0), direction.x, direction.z)
+    } else {
+        (4 + usize::from(direction.z < 0.0), direction.x, direction.y)
+    };
+    face * 4 + usize::from(u < 0.0) * 2 + usize::from(v < 0.0)
+}
+
+/// Recursive least squares estimator with exponential forgetting.
+#[derive(Debug, Clone)]
+struct RecursiveLeastSquares<const N: usize> {
+    theta: [f64; N],
+    covariance: [[f64; N]; N],
+    forgetting_factor: f64,
+}
+
+impl<const N: usize> RecursiveLeastSquares<N> {
+    const INITIAL_COVARIANCE: f64 = 1e8;
+
+    fn new(forgetting_factor: f64) -> Self {
+        let mut covariance = [[0.0; N]; N];
+        for (index, row) in covariance.iter_mut().enumerate() {
+            row[index] = Self::INITIAL_COVARIANCE;
+        }
+        Self {
+            theta: [0.0; N],
+            covariance,
+            forgetting_factor,
+        }
+    }
+
+    fn update(&mut self, regressors: [f64; N], measurement: f64) {
+        let mut gain = [0.0; N];
+        for (gain, row) in gain.iter_mut().zip(&self.covariance) {
+            *gain = row.iter().zip(&regressors).map(|(p, x)| p * x).sum();
+        }
+        let denominator = self.forgetting_factor
+            + regressors
+                .iter()
+                .zip(&gain)
+                .map(|(x, g)| x * g)
+                .sum::<f64>();
+        if denominator <= 0.0 || !denominator.is_finite() {
+            return;
+        }
+        for gain in gain.iter_mut() {
+            *gain /= denominator;
+        }
+
+        let error = measurement
+            - regressors
+                .iter()
+                .zip(&self.theta)
+                .map(|(x, theta)| x * theta)
+                .sum::<f64>();
+        for (theta, gain) in self.theta.iter_mut().zip(&gain) {
+            *theta += gain * error;
+        }
+
+        // P = (P - k xᵀ P) / λ, using the symmetry of P
+        let mut projected = [0.0; N];
+        for (column, projected) in projected.iter_mut().enumerate() {
+            *projected = regressors
+                .iter()
+                .zip(&self.covariance)
+                .map(|(x, row)| x * row[column])
+                .sum();
+        }
+        for (row, gain) in self.covariance.iter_mut().zip(&gain) {
+            for (p, projected) in row.iter_mut().zip(&projected) {
+                *p = (*p - gain * projected) / self.forgetting_factor;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD_STRENGTH: f32 = 50.0;
+
+    /// Returns directions spiralling over the whole sphere.
+    fn directions(count: usize) -> impl Iterator<Item = Vector> {
+        let golden_angle = core::f32::consts::PI * (3.0 - libm::sqrtf(5.0));
+        (0..count).map(move |index| {
+            let z = 1.0 - 2.0 * (index as f32 + 0.5) / count as f32;
+            let radius = libm::sqrtf(1.0 - z * z);
+            let angle = golden_angle * index as f32;
+            Vector::new(radius * libm::cosf(angle), radius * libm::sinf(angle), z)
+        })
+    }
+
+    #[test]
+    fn hard_iron_is_estimated() {
+        let hard_iron = Vector::new(10.0, -5.0, 20.0);
+        let mut calibrator = MagneticCalibrator::default();
+        let mut published = None;
+
+        // Act
+        for direction in directions(400) {
+            if let Some(calibration) = calibrator.update(direction * FIELD_STRENGTH + hard_iron) {
+                published = Some(calibration);
+            }
+        }
+
+        let calibration = published.unwrap();
+        assert!((calibration.hard_iron - hard_iron).magnitude() < 0.1);
+        assert!(libm::fabsf(calibrator.field_strength() - FIELD_STRENGTH) < 0.1);
+        assert!(calibrator.quality().coverage > 0.99);
+    }
+
+    #[test]
+    fn soft_iron_is_estimated() {
+        let hard_iron = Vector::new(-8.0, 3.0, 12.0);
+        let distortion = Matrix::from_rows(
+            Vector::new(1.1, 0.05, 0.0),
+            Vector::new(0.05, 0.9, -0.03),
+            Vector::new(0.0, -0.03, 1.0),
+        );
+        let mut calibrator = MagneticCalibrator::new(MagneticCalibratorSettings {
+            soft_iron: true,
+            ..Default::default()
+        });
+
+        // Act
+        for direction in directions(600) {
+            calibrator.update(distortion * (direction * FIELD_STRENGTH) + hard_iron);
+        }
+
+        let calibration = calibrator.estimate();
+        assert!((calibration.hard_iron - hard_iron).magnitude() < 0.1);
+        for direction in directions(50) {
+            let measured = distortion * (direction * FIELD_STRENGTH) + hard_iron;
+            let magnitude = calibration.apply(measured).magnitude();
+            assert!(libm::fabsf(magnitude / calibrator.field_strength() - 1.0) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn stationary_samples_are_not_published() {
+        let mut calibrator = MagneticCalibrator::default();
+
+        // Act
+        let published = (0..1000)
+            .filter_map(|_| calibrator.update(Vector::new(30.0, 0.0, -40.0)))
+            .count();
+
+        assert_eq!(published, 0);
+        assert_eq!(calibrator.quality().samples, 1);
+    }
+}
@@ -1,8 +1,10 @@
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
 use fusion_imu_sys as sys;
 
 /// 3D vector.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 #[repr(C)]
@@ -13,10 +15,115 @@ pub struct Vector {
 }
 
 impl Vector {
+    /// Vector of zeros.
+    pub const ZERO: Vector = Vector::new(0.0, 0.0, 0.0);
+
+    /// Vector of ones.
+    pub const ONES: Vector = Vector::new(1.0, 1.0, 1.0);
+
     /// Create a new `Vector`.
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
         Self { x, y, z }
     }
+
+    /// Returns true if all elements are zero.
+    pub fn is_zero(self) -> bool {
+        self.x == 0.0 && self.y == 0.0 && self.z == 0.0
+    }
+
+    /// Returns the dot product of two vectors.
+    pub fn dot(self, other: Vector) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Returns the cross product of two vectors.
+    pub fn cross(self, other: Vector) -> Vector {
+        Vector {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// Returns the element-wise product of two vectors.
+    pub fn hadamard_product(self, other: Vector) -> Vector {
+        Vector {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+
+    /// Returns the vector magnitude squared.
+    pub fn magnitude_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Returns the vector magnitude.
+    pub fn magnitude(self) -> f32 {
+        libm::sqrtf(self.magnitude_squared())
+    }
+
+    /// Returns the normalised vector. A zero vector is returned unchanged.
+    pub fn normalise(self) -> Vector {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            return self;
+        }
+        self / magnitude
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Vector {
+        Vector::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, rhs: Vector) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: f32) -> Vector {
+        Vector::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Div<f32> for Vector {
+    type Output = Vector;
+
+    fn div(self, rhs: f32) -> Vector {
+        Vector::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
 }
 
 impl From<sys::FusionVector> for Vector {
@@ -52,10 +159,168 @@ pub struct Quaternion {
 }
 
 impl Quaternion {
+    /// Identity quaternion.
+    pub const IDENTITY: Quaternion = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+
+    /// Create a new `Quaternion`.
+    pub const fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Create a new `Quaternion` describing a rotation about an axis by an
+    /// angle in degrees.
+    pub fn from_axis_angle(axis: Vector, angle: f32) -> Self {
+        let half_angle = 0.5 * angle.to_radians();
+        let axis = axis.normalise() * libm::sinf(half_angle);
+        Self::new(libm::cosf(half_angle), axis.x, axis.y, axis.z)
+    }
+
+    /// Create a new `Quaternion` from a rotation matrix.
+    pub fn from_matrix(matrix: Matrix) -> Self {
+        let m = matrix;
+        let trace = m.xx + m.yy + m.zz;
+        let quaternion = if trace > 0.0 {
+            let s = 0.5 / libm::sqrtf(trace + 1.0);
+            Self::new(
+                0.25 / s,
+                (m.zy - m.yz) * s,
+                (m.xz - m.zx) * s,
+                (m.yx - m.xy) * s,
+            )
+        } else if m.xx > m.yy && m.xx > m.zz {
+            let s = 2.0 * libm::sqrtf(1.0 + m.xx - m.yy - m.zz);
+            Self::new(
+                (m.zy - m.yz) / s,
+                0.25 * s,
+                (m.xy + m.yx) / s,
+                (m.xz + m.zx) / s,
+            )
+        } else if m.yy > m.zz {
+            let s = 2.0 * libm::sqrtf(1.0 + m.yy - m.xx - m.zz);
+            Self::new(
+                (m.xz - m.zx) / s,
+                (m.xy + m.yx) / s,
+                0.25 * s,
+                (m.yz + m.zy) / s,
+            )
+        } else {
+            let s = 2.0 * libm::sqrtf(1.0 + m.zz - m.xx - m.yy);
+            Self::new(
+                (m.yx - m.xy) / s,
+                (m.xz + m.zx) / s,
+                (m.yz + m.zy) / s,
+                0.25 * s,
+            )
+        };
+        quaternion.normalise()
+    }
+
     /// Converts a quaternion to ZYX Euler angles in degrees.
     pub fn to_euler(self) -> Euler {
         unsafe { sys::FusionQuaternionToEuler(self.into()).into() }
     }
+
+    /// Converts a quaternion to a rotation matrix.
+    pub fn to_matrix(self) -> Matrix {
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        Matrix::from_rows(
+            Vector::new(
+                2.0 * (w * w - 0.5 + x * x),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ),
+            Vector::new(
+                2.0 * (x * y + w * z),
+                2.0 * (w * w - 0.5 + y * y),
+                2.0 * (y * z - w * x),
+            ),
+            Vector::new(
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                2.0 * (w * w - 0.5 + z * z),
+            ),
+        )
+    }
+
+    /// Returns the conjugate quaternion, which describes the inverse rotation
+    /// of a unit quaternion.
+    pub fn conjugate(self) -> Quaternion {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Returns the normalised quaternion.
+    pub fn normalise(self) -> Quaternion {
+        let magnitude =
+            libm::sqrtf(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z);
+        if magnitude == 0.0 {
+            return self;
+        }
+        Self::new(
+            self.w / magnitude,
+            self.x / magnitude,
+            self.y / magnitude,
+            self.z / magnitude,
+        )
+    }
+
+    /// Rotates a vector by the quaternion.
+    pub fn rotate(self, vector: Vector) -> Vector {
+        self.to_matrix() * vector
+    }
+
+    /// Returns the twist of the rotation about an axis from the swing-twist
+    /// decomposition. The swing is `self * twist.conjugate()` in the rotated
+    /// frame or `twist.conjugate() * self` in the reference frame.
+    pub fn twist(self, axis: Vector) -> Quaternion {
+        let axis = axis.normalise();
+        let projection = axis * axis.dot(Vector::new(self.x, self.y, self.z));
+        let twist = Quaternion::new(self.w, projection.x, projection.y, projection.z);
+        if twist.w == 0.0 && projection.is_zero() {
+            return Quaternion::IDENTITY;
+        }
+        twist.normalise()
+    }
+
+    /// Returns the angle in degrees of the rotation between two quaternions.
+    pub fn angle_to(self, other: Quaternion) -> f32 {
+        let dot = self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z;
+        2.0 * libm::acosf(libm::fabsf(dot).min(1.0)).to_degrees()
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        let (a, b) = (self, rhs);
+        Quaternion::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+}
+
+impl Add for Quaternion {
+    type Output = Quaternion;
+
+    fn add(self, rhs: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w + rhs.w,
+            self.x + rhs.x,
+            self.y + rhs.y,
+            self.z + rhs.z,
+        )
+    }
+}
+
+impl Mul<f32> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: f32) -> Quaternion {
+        Quaternion::new(self.w * rhs, self.x * rhs, self.y * rhs, self.z * rhs)
+    }
 }
 
 impl From<sys::FusionQuaternion> for Quaternion {
@@ -98,6 +363,190 @@ pub struct Matrix {
     pub zz: f32,
 }
 
+impl Matrix {
+    /// Identity matrix.
+    pub const IDENTITY: Matrix = Matrix::from_rows(
+        Vector::new(1.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+        Vector::new(0.0, 0.0, 1.0),
+    );
+
+    /// Create a new `Matrix` from three row vectors.
+    pub const fn from_rows(x: Vector, y: Vector, z: Vector) -> Self {
+        Self {
+            xx: x.x,
+            xy: x.y,
+            xz: x.z,
+            yx: y.x,
+            yy: y.y,
+            yz: y.z,
+            zx: z.x,
+            zy: z.y,
+            zz: z.z,
+        }
+    }
+
+    /// Create a new `Matrix` from three column vectors.
+    pub const fn from_columns(x: Vector, y: Vector, z: Vector) -> Self {
+        Self {
+            xx: x.x,
+            xy: y.x,
+            xz: z.x,
+            yx: x.y,
+            yy: y.y,
+            yz: z.y,
+            zx: x.z,
+            zy: y.z,
+            zz: z.z,
+        }
+    }
+
+    /// Create a new diagonal `Matrix`.
+    pub const fn from_diagonal(diagonal: Vector) -> Self {
+        Self::from_rows(
+            Vector::new(diagonal.x, 0.0, 0.0),
+            Vector::new(0.0, diagonal.y, 0.0),
+            Vector::new(0.0, 0.0, diagonal.z),
+        )
+    }
+
+    /// Returns the rows of the matrix.
+    pub fn rows(self) -> [Vector; 3] {
+        [
+            Vector::new(self.xx, self.xy, self.xz),
+            Vector::new(self.yx, self.yy, self.yz),
+            Vector::new(self.zx, self.zy, self.zz),
+        ]
+    }
+
+    /// Returns the columns of the matrix.
+    pub fn columns(self) -> [Vector; 3] {
+        self.transpose().rows()
+    }
+
+    /// Returns the transposed matrix.
+    pub fn transpose(self) -> Matrix {
+        Matrix::from_columns(
+            Vector::new(self.xx, self.xy, self.xz),
+            Vector::new(self.yx, self.yy, self.yz),
+            Vector::new(self.zx, self.zy, self.zz),
+        )
+    }
+
+    /// Returns the matrix determinant.
+    pub fn determinant(self) -> f32 {
+        let [x, y, z] = self.rows();
+        x.dot(y.cross(z))
+    }
+
+    /// Returns the inverse of the matrix, or `None` if the matrix is
+    /// singular.
+    pub fn inverse(self) -> Option<Matrix> {
+        let [x, y, z] = self.rows();
+        let determinant = x.dot(y.cross(z));
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        Some(Matrix::from_columns(y.cross(z), z.cross(x), x.cross(y)) * (1.0 / determinant))
+    }
+}
+
+/// Returns the eigenvalues and the matching eigenvectors (as columns) of a
+/// symmetric matrix using Jacobi rotations.
+pub(crate) fn symmetric_eigen(matrix: Matrix) -> (Vector, Matrix) {
+    let mut a = [
+        [matrix.xx, matrix.xy, matrix.xz],
+        [matrix.yx, matrix.yy, matrix.yz],
+        [matrix.zx, matrix.zy, matrix.zz],
+    ];
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..32 {
+        let off_diagonal = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off_diagonal < 1e-18 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (libm::fabsf(theta) + libm::sqrtf(theta * theta + 1.0));
+            let c = 1.0 / libm::sqrtf(t * t + 1.0);
+            let s = t * c;
+            for row in a.iter_mut() {
+                let (akp, akq) = (row[p], row[q]);
+                row[p] = c * akp - s * akq;
+                row[q] = s * akp + c * akq;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            for (k, (apk, aqk)) in row_p.into_iter().zip(row_q).enumerate() {
+                a[p][k] = c * apk - s * aqk;
+                a[q][k] = s * apk + c * aqk;
+            }
+            for row in v.iter_mut() {
+                let (vkp, vkq) = (row[p], row[q]);
+                row[p] = c * vkp - s * vkq;
+                row[q] = s * vkp + c * vkq;
+            }
+        }
+    }
+    (
+        Vector::new(a[0][0], a[1][1], a[2][2]),
+        Matrix::from_rows(
+            Vector::new(v[0][0], v[0][1], v[0][2]),
+            Vector::new(v[1][0], v[1][1], v[1][2]),
+            Vector::new(v[2][0], v[2][1], v[2][2]),
+        ),
+    )
+}
+
+impl Add for Matrix {
+    type Output = Matrix;
+
+    fn add(self, rhs: Matrix) -> Matrix {
+        let [a, b, c] = self.rows();
+        let [d, e, f] = rhs.rows();
+        Matrix::from_rows(a + d, b + e, c + f)
+    }
+}
+
+impl Sub for Matrix {
+    type Output = Matrix;
+
+    fn sub(self, rhs: Matrix) -> Matrix {
+        let [a, b, c] = self.rows();
+        let [d, e, f] = rhs.rows();
+        Matrix::from_rows(a - d, b - e, c - f)
+    }
+}
+
+impl Mul<f32> for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: f32) -> Matrix {
+        let [x, y, z] = self.rows();
+        Matrix::from_rows(x * rhs, y * rhs, z * rhs)
+    }
+}
+
+impl Mul<Vector> for Matrix {
+    type Output = Vector;
+
+    fn mul(self, rhs: Vector) -> Vector {
+        let [x, y, z] = self.rows();
+        Vector::new(x.dot(rhs), y.dot(rhs), z.dot(rhs))
+    }
+}
+
+impl Mul<Matrix> for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        let [x, y, z] = rhs.columns();
+        Matrix::from_columns(self * x, self * y, self * z)
+    }
+}
+
 impl From<sys::FusionMatrix> for Matrix {
     fn from(value: sys::FusionMatrix) -> Self {
         let values: sys::FusionMatrix__bindgen_ty_1 = unsafe { value.element };
@@ -161,9 +610,33 @@ impl From<Euler> for sys::FusionEuler {
     }
 }
 
+/// Sensor axis.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    /// Returns the unit vector along the axis.
+    pub fn unit_vector(self) -> Vector {
+        match self {
+            Axis::X => Vector::new(1.0, 0.0, 0.0),
+            Axis::Y => Vector::new(0.0, 1.0, 0.0),
+            Axis::Z => Vector::new(0.0, 0.0, 1.0),
+        }
+    }
+}
+
 /// Earth axes convention.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 #[repr(C)]
 pub enum Convention {
     #[default]
@@ -513,4 +986,98 @@ mod tests {
         assert_eq!(values.pitch, 2.0);
         assert_eq!(values.yaw, 3.0);
     }
+
+    #[test]
+    fn vector_cross_product_is_right_handed() {
+        let x = Axis::X.unit_vector();
+        let y = Axis::Y.unit_vector();
+
+        // Act
+        let z = x.cross(y);
+
+        assert_eq!(z, Axis::Z.unit_vector());
+    }
+
+    #[test]
+    fn matrix_multiplied_by_inverse_is_identity() {
+        let matrix = Matrix::from_rows(
+            Vector::new(2.0, 0.5, 0.0),
+            Vector::new(0.1, 1.0, -0.3),
+            Vector::new(0.0, 0.2, 4.0),
+        );
+
+        // Act
+        let product = matrix * matrix.inverse().unwrap();
+
+        for (row, expected) in product.rows().iter().zip(Matrix::IDENTITY.rows()) {
+            assert!((*row - expected).magnitude() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn symmetric_eigen_reconstructs_matrix() {
+        let matrix = Matrix::from_rows(
+            Vector::new(4.0, 1.0, -0.5),
+            Vector::new(1.0, 3.0, 0.2),
+            Vector::new(-0.5, 0.2, 2.0),
+        );
+
+        // Act
+        let (values, vectors) = symmetric_eigen(matrix);
+
+        let reconstructed = vectors * Matrix::from_diagonal(values) * vectors.transpose();
+        for (row, expected) in reconstructed.rows().iter().zip(matrix.rows()) {
+            assert!((*row - expected).magnitude() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn quaternion_maps_to_and_from_matrix() {
+        let quaternion = Quaternion::from_axis_angle(Vector::new(1.0, -2.0, 0.5), 130.0);
+
+        // Act
+        let result = Quaternion::from_matrix(quaternion.to_matrix());
+
+        assert!(result.angle_to(quaternion) < 1e-2);
+    }
+
+    #[test]
+    fn quaternion_product_composes_rotations() {
+        let first = Quaternion::from_axis_angle(Axis::Z.unit_vector(), 90.0);
+        let second = Quaternion::from_axis_angle(Axis::X.unit_vector(), 90.0);
+
+        // Act
+        let rotated = (first * second).rotate(Axis::Y.unit_vector());
+
+        assert!((rotated - Axis::Z.unit_vector()).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        let matrix = Matrix::from_rows(
+            Vector::new(1.0, 2.0, 3.0),
+            Vector::new(2.0, 4.0, 6.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+
+        // Act
+        let inverse = matrix.inverse();
+
+        assert_eq!(inverse, None);
+    }
+
+    #[test]
+    fn twist_removes_swing() {
+        let twist = Quaternion::from_axis_angle(Axis::Z.unit_vector(), 70.0);
+        let swing = Quaternion::from_axis_angle(Vector::new(1.0, 1.0, 0.0), 30.0);
+
+        // Act
+        let result = (twist * swing).twist(Axis::Z.unit_vector());
+
+        assert!(result.angle_to(twist) < 1e-3);
+        assert_eq!(
+            Quaternion::new(0.0, 1.0, 0.0, 0.0).twist(Axis::Z.unit_vector()),
+            Quaternion::IDENTITY
+        );
+    }
 }
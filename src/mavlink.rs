@@ -0,0 +1,707 @@
+//! MAVLink v2 message encoding and parsing.
+//!
+//! Supports the `ATTITUDE`, `ATTITUDE_QUATERNION`, `SCALED_IMU`, and
+//! `HIGHRES_IMU` messages of the common message set without allocating.
+//! MAVLink uses the North-East-Down convention, so the AHRS algorithm should
+//! be configured with [`Convention::NorthWestDown`](crate::Convention).
+
+use core::f32::consts::PI;
+use core::fmt;
+
+use crate::{Ahrs, Quaternion, Vector};
+
+/// Start byte of a MAVLink v2 frame.
+pub const STX: u8 = 0xfd;
+
+/// Maximum payload length.
+pub const MAX_PAYLOAD_LENGTH: usize = 255;
+
+/// Maximum length of an unsigned frame.
+pub const MAX_FRAME_LENGTH: usize = HEADER_LENGTH + MAX_PAYLOAD_LENGTH + 2;
+
+const HEADER_LENGTH: usize = 10;
+const SIGNATURE_LENGTH: usize = 13;
+const INCOMPAT_FLAG_SIGNED: u8 = 0x01;
+const STANDARD_GRAVITY: f32 = 9.80665;
+
+/// MAVLink message.
+pub trait Message: Sized {
+    /// Message ID.
+    const ID: u32;
+    /// CRC seed derived from the message definition.
+    const CRC_EXTRA: u8;
+
+    /// Writes the payload in wire order and returns its untruncated length.
+    fn serialize(&self, payload: &mut [u8; MAX_PAYLOAD_LENGTH]) -> usize;
+
+    /// Reads the payload in wire order. Truncated fields are zero.
+    fn deserialize(payload: &[u8; MAX_PAYLOAD_LENGTH]) -> Self;
+}
+
+/// Encoded frame.
+#[derive(Clone, Copy)]
+pub struct Frame {
+    buffer: [u8; MAX_FRAME_LENGTH],
+    length: usize,
+}
+
+impl Frame {
+    /// Returns the frame bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.length]
+    }
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Frame").field(&self.as_bytes()).finish()
+    }
+}
+
+/// Encoder of MAVLink v2 frames with a running sequence number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MavlinkEncoder {
+    system_id: u8,
+    component_id: u8,
+    sequence: u8,
+}
+
+impl MavlinkEncoder {
+    /// Create a new `MavlinkEncoder` instance.
+    ///
+    /// Arguments:
+    /// - `system_id`: ID of the sending system.
+    /// - `component_id`: ID of the sending component.
+    pub fn new(system_id: u8, component_id: u8) -> Self {
+        Self {
+            system_id,
+            component_id,
+            sequence: 0,
+        }
+    }
+
+    /// Returns the sequence number of the next frame.
+    pub fn sequence(&self) -> u8 {
+        self.sequence
+    }
+
+    /// Encodes a message into a frame. Trailing zero bytes of the payload are
+    /// truncated.
+    pub fn encode<M: Message>(&mut self, message: &M) -> Frame {
+        let mut payload = [0; MAX_PAYLOAD_LENGTH];
+        let mut length = message.serialize(&mut payload);
+        while length > 1 && payload[length - 1] == 0 {
+            length -= 1;
+        }
+
+        let mut buffer = [0; MAX_FRAME_LENGTH];
+        let id = M::ID.to_le_bytes();
+        buffer[..HEADER_LENGTH].copy_from_slice(&[
+            STX,
+            length as u8,
+            0,
+            0,
+            self.sequence,
+            self.system_id,
+            self.component_id,
+            id[0],
+            id[1],
+            id[2],
+        ]);
+        buffer[HEADER_LENGTH..HEADER_LENGTH + length].copy_from_slice(&payload[..length]);
+        let end = HEADER_LENGTH + length;
+        let crc = checksum(&buffer[1..end], M::CRC_EXTRA);
+        buffer[end..end + 2].copy_from_slice(&crc.to_le_bytes());
+        self.sequence = self.sequence.wrapping_add(1);
+        Frame {
+            buffer,
+            length: end + 2,
+        }
+    }
+}
+
+/// Header fields of a parsed frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct FrameHeader {
+    /// Sequence number.
+    pub sequence: u8,
+    /// ID of the sending system.
+    pub system_id: u8,
+    /// ID of the sending component.
+    pub component_id: u8,
+    /// Total frame length in bytes, including any signature.
+    pub length: usize,
+}
+
+/// Error parsing a MAVLink frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MavlinkError {
+    /// The first byte is not the MAVLink v2 start byte.
+    InvalidStart,
+    /// The bytes end before the end of the frame.
+    Truncated,
+    /// The checksum does not match.
+    Crc,
+    /// The frame contains a different message.
+    UnexpectedMessage(u32),
+}
+
+impl fmt::Display for MavlinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidStart => write!(f, "invalid start byte"),
+            Self::Truncated => write!(f, "truncated frame"),
+            Self::Crc => write!(f, "checksum mismatch"),
+            Self::UnexpectedMessage(id) => write!(f, "unexpected message ID {id}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MavlinkError {}
+
+/// Parses a message from a frame at the start of the bytes. Signatures of
+/// signed frames are skipped but not verified.
+pub fn parse<M: Message>(bytes: &[u8]) -> Result<(FrameHeader, M), MavlinkError> {
+    if bytes.first() != Some(&STX) {
+        return Err(MavlinkError::InvalidStart);
+    }
+    let header = bytes.get(..HEADER_LENGTH).ok_or(MavlinkError::Truncated)?;
+    let payload_length = header[1] as usize;
+    let signed = header[2] & INCOMPAT_FLAG_SIGNED != 0;
+    let end = HEADER_LENGTH + payload_length;
+    let length = end + 2 + if signed { SIGNATURE_LENGTH } else { 0 };
+    if bytes.len() < length {
+        return Err(MavlinkError::Truncated);
+    }
+    let id = u32::from_le_bytes([header[7], header[8], header[9], 0]);
+    if id != M::ID {
+        return Err(MavlinkError::UnexpectedMessage(id));
+    }
+    let crc = u16::from_le_bytes([bytes[end], bytes[end + 1]]);
+    if checksum(&bytes[1..end], M::CRC_EXTRA) != crc {
+        return Err(MavlinkError::Crc);
+    }
+
+    let mut payload = [0; MAX_PAYLOAD_LENGTH];
+    payload[..payload_length].copy_from_slice(&bytes[HEADER_LENGTH..end]);
+    let header = FrameHeader {
+        sequence: header[4],
+        system_id: header[5],
+        component_id: header[6],
+        length,
+    };
+    Ok((header, M::deserialize(&payload)))
+}
+
+/// `ATTITUDE` message (ID 30).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Attitude {
+    /// Time since system boot in milliseconds.
+    pub time_boot_ms: u32,
+    /// Roll angle in radians.
+    pub roll: f32,
+    /// Pitch angle in radians.
+    pub pitch: f32,
+    /// Yaw angle in radians.
+    pub yaw: f32,
+    /// Roll angular speed in radians per second.
+    pub rollspeed: f32,
+    /// Pitch angular speed in radians per second.
+    pub pitchspeed: f32,
+    /// Yaw angular speed in radians per second.
+    pub yawspeed: f32,
+}
+
+impl Attitude {
+    /// Create a new `Attitude` instance from the AHRS algorithm output.
+    ///
+    /// Arguments:
+    /// - `time_boot_ms`: Time since system boot in milliseconds.
+    /// - `ahrs`: AHRS algorithm.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    pub fn from_ahrs(time_boot_ms: u32, ahrs: &(impl Ahrs + ?Sized), gyroscope: Vector) -> Self {
+        let euler = ahrs.quaternion().to_euler();
+        let rates = gyroscope * (PI / 180.0);
+        Self {
+            time_boot_ms,
+            roll: euler.roll.to_radians(),
+            pitch: euler.pitch.to_radians(),
+            yaw: euler.yaw.to_radians(),
+            rollspeed: rates.x,
+            pitchspeed: rates.y,
+            yawspeed: rates.z,
+        }
+    }
+}
+
+impl Message for Attitude {
+    const ID: u32 = 30;
+    const CRC_EXTRA: u8 = 39;
+
+    fn serialize(&self, payload: &mut [u8; MAX_PAYLOAD_LENGTH]) -> usize {
+        let mut writer = Writer::new(payload);
+        writer.u32(self.time_boot_ms);
+        for value in [
+            self.roll,
+            self.pitch,
+            self.yaw,
+            self.rollspeed,
+            self.pitchspeed,
+            self.yawspeed,
+        ] {
+            writer.f32(value);
+        }
+        writer.position
+    }
+
+    fn deserialize(payload: &[u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        let mut reader = Reader::new(payload);
+        Self {
+            time_boot_ms: reader.u32(),
+            roll: reader.f32(),
+            pitch: reader.f32(),
+            yaw: reader.f32(),
+            rollspeed: reader.f32(),
+            pitchspeed: reader.f32(),
+            yawspeed: reader.f32(),
+        }
+    }
+}
+
+/// `ATTITUDE_QUATERNION` message (ID 31).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AttitudeQuaternion {
+    /// Time since system boot in milliseconds.
+    pub time_boot_ms: u32,
+    /// Quaternion describing the sensor relative to the Earth.
+    pub quaternion: Quaternion,
+    /// Roll angular speed in radians per second.
+    pub rollspeed: f32,
+    /// Pitch angular speed in radians per second.
+    pub pitchspeed: f32,
+    /// Yaw angular speed in radians per second.
+    pub yawspeed: f32,
+}
+
+impl AttitudeQuaternion {
+    /// Create a new `AttitudeQuaternion` instance from the AHRS algorithm
+    /// output.
+    ///
+    /// Arguments:
+    /// - `time_boot_ms`: Time since system boot in milliseconds.
+    /// - `ahrs`: AHRS algorithm.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    pub fn from_ahrs(time_boot_ms: u32, ahrs: &(impl Ahrs + ?Sized), gyroscope: Vector) -> Self {
+        let rates = gyroscope * (PI / 180.0);
+        Self {
+            time_boot_ms,
+            quaternion: ahrs.quaternion(),
+            rollspeed: rates.x,
+            pitchspeed: rates.y,
+            yawspeed: rates.z,
+        }
+    }
+}
+
+impl Message for AttitudeQuaternion {
+    const ID: u32 = 31;
+    const CRC_EXTRA: u8 = 246;
+
+    fn serialize(&self, payload: &mut [u8; MAX_PAYLOAD_LENGTH]) -> usize {
+        let mut writer = Writer::new(payload);
+        let quaternion = self.quaternion;
+        writer.u32(self.time_boot_ms);
+        for value in [
+            quaternion.w,
+            quaternion.x,
+            quaternion.y,
+            quaternion.z,
+            self.rollspeed,
+            self.pitchspeed,
+            self.yawspeed,
+        ] {
+            writer.f32(value);
+        }
+        writer.position
+    }
+
+    fn deserialize(payload: &[u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        let mut reader = Reader::new(payload);
+        Self {
+            time_boot_ms: reader.u32(),
+            quaternion: Quaternion::new(reader.f32(), reader.f32(), reader.f32(), reader.f32()),
+            rollspeed: reader.f32(),
+            pitchspeed: reader.f32(),
+            yawspeed: reader.f32(),
+        }
+    }
+}
+
+/// `SCALED_IMU` message (ID 26).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ScaledImu {
+    /// Time since system boot in milliseconds.
+    pub time_boot_ms: u32,
+    /// Acceleration in milli-g.
+    pub acceleration: [i16; 3],
+    /// Angular rate in milliradians per second.
+    pub angular_rate: [i16; 3],
+    /// Magnetic field in milligauss.
+    pub magnetic_field: [i16; 3],
+    /// Temperature in centidegrees Celsius, or 0 if not available.
+    pub temperature: i16,
+}
+
+impl ScaledImu {
+    /// Create a new `ScaledImu` instance from sensor measurements. Values
+    /// outside the range of the message saturate.
+    ///
+    /// Arguments:
+    /// - `time_boot_ms`: Time since system boot in milliseconds.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `magnetometer`: Magnetometer measurement in microtesla, if available.
+    pub fn from_measurements(
+        time_boot_ms: u32,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        magnetometer: Option<Vector>,
+    ) -> Self {
+        let scale = |vector: Vector, scale: f32| {
+            let vector = vector * scale;
+            [vector.x as i16, vector.y as i16, vector.z as i16]
+        };
+        Self {
+            time_boot_ms,
+            acceleration: scale(accelerometer, 1000.0),
+            angular_rate: scale(gyroscope, PI / 180.0 * 1000.0),
+            magnetic_field: scale(magnetometer.unwrap_or_default(), 10.0),
+            temperature: 0,
+        }
+    }
+}
+
+impl Message for ScaledImu {
+    const ID: u32 = 26;
+    const CRC_EXTRA: u8 = 170;
+
+    fn serialize(&self, payload: &mut [u8; MAX_PAYLOAD_LENGTH]) -> usize {
+        let mut writer = Writer::new(payload);
+        writer.u32(self.time_boot_ms);
+        for values in [self.acceleration, self.angular_rate, self.magnetic_field] {
+            for value in values {
+                writer.i16(value);
+            }
+        }
+        writer.i16(self.temperature);
+        writer.position
+    }
+
+    fn deserialize(payload: &[u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        let mut reader = Reader::new(payload);
+        Self {
+            time_boot_ms: reader.u32(),
+            acceleration: [reader.i16(), reader.i16(), reader.i16()],
+            angular_rate: [reader.i16(), reader.i16(), reader.i16()],
+            magnetic_field: [reader.i16(), reader.i16(), reader.i16()],
+            temperature: reader.i16(),
+        }
+    }
+}
+
+/// `HIGHRES_IMU` message (ID 105).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct HighresImu {
+    /// Timestamp in microseconds.
+    pub time_usec: u64,
+    /// Acceleration in metres per second squared.
+    pub acceleration: Vector,
+    /// Angular rate in radians per second.
+    pub angular_rate: Vector,
+    /// Magnetic field in gauss.
+    pub magnetic_field: Vector,
+    /// Absolute pressure in hectopascal.
+    pub abs_pressure: f32,
+    /// Differential pressure in hectopascal.
+    pub diff_pressure: f32,
+    /// Altitude calculated from pressure.
+    pub pressure_alt: f32,
+    /// Temperature in degrees Celsius.
+    pub temperature: f32,
+    /// Bitmap of the updated fields.
+    pub fields_updated: u16,
+    /// IMU ID.
+    pub id: u8,
+}
+
+impl HighresImu {
+    /// Returns the gyroscope measurement in degrees per second.
+    pub fn gyroscope(&self) -> Vector {
+        self.angular_rate * (180.0 / PI)
+    }
+
+    /// Returns the accelerometer measurement in g.
+    pub fn accelerometer(&self) -> Vector {
+        self.acceleration / STANDARD_GRAVITY
+    }
+
+    /// Returns the magnetometer measurement in microtesla.
+    pub fn magnetometer(&self) -> Vector {
+        self.magnetic_field * 100.0
+    }
+}
+
+impl Message for HighresImu {
+    const ID: u32 = 105;
+    const CRC_EXTRA: u8 = 93;
+
+    fn serialize(&self, payload: &mut [u8; MAX_PAYLOAD_LENGTH]) -> usize {
+        let mut writer = Writer::new(payload);
+        writer.u64(self.time_usec);
+        for vector in [self.acceleration, self.angular_rate, self.magnetic_field] {
+            writer.vector(vector);
+        }
+        for value in [
+            self.abs_pressure,
+            self.diff_pressure,
+            self.pressure_alt,
+            self.temperature,
+        ] {
+            writer.f32(value);
+        }
+        writer.u16(self.fields_updated);
+        writer.u8(self.id);
+        writer.position
+    }
+
+    fn deserialize(payload: &[u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        let mut reader = Reader::new(payload);
+        Self {
+            time_usec: reader.u64(),
+            acceleration: reader.vector(),
+            angular_rate: reader.vector(),
+            magnetic_field: reader.vector(),
+            abs_pressure: reader.f32(),
+            diff_pressure: reader.f32(),
+            pressure_alt: reader.f32(),
+            temperature: reader.f32(),
+            fields_updated: reader.u16(),
+            id: reader.u8(),
+        }
+    }
+}
+
+/// X.25 checksum of the frame bytes after the start byte, seeded with the
+/// CRC_EXTRA byte.
+fn checksum(bytes: &[u8], crc_extra: u8) -> u16 {
+    bytes
+        .iter()
+        .chain(&[crc_extra])
+        .fold(0xffff, |crc: u16, &byte| {
+            let mut tmp = byte ^ crc as u8;
+            tmp ^= tmp << 4;
+            let tmp = tmp as u16;
+            (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+        })
+}
+
+/// Little-endian writer into a payload.
+struct Writer<'a> {
+    payload: &'a mut [u8; MAX_PAYLOAD_LENGTH],
+    position: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(payload: &'a mut [u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        Self {
+            payload,
+            position: 0,
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.payload[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn vector(&mut self, vector: Vector) {
+        for value in [vector.x, vector.y, vector.z] {
+            self.f32(value);
+        }
+    }
+}
+
+/// Little-endian reader of a payload.
+struct Reader<'a> {
+    payload: &'a [u8; MAX_PAYLOAD_LENGTH],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(payload: &'a [u8; MAX_PAYLOAD_LENGTH]) -> Self {
+        Self {
+            payload,
+            position: 0,
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.payload[self.position..self.position + N]);
+        self.position += N;
+        bytes
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.take())
+    }
+
+    fn i16(&mut self) -> i16 {
+        i16::from_le_bytes(self.take())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    fn f32(&mut self) -> f32 {
+        f32::from_le_bytes(self.take())
+    }
+
+    fn vector(&mut self) -> Vector {
+        Vector::new(self.f32(), self.f32(), self.f32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FusionAhrs;
+
+    #[test]
+    fn checksum_matches_reference() {
+        // Act
+        let crc = checksum(b"12345678", b'9');
+
+        assert_eq!(crc, 0x6f91);
+    }
+
+    #[test]
+    fn attitude_frame_layout() {
+        let mut encoder = MavlinkEncoder::new(1, 200);
+        let ahrs = FusionAhrs::new();
+        let attitude = Attitude::from_ahrs(1000, &ahrs, Vector::new(0.0, 0.0, 180.0));
+
+        // Act
+        encoder.encode(&attitude);
+        let frame = encoder.encode(&attitude);
+
+        let bytes = frame.as_bytes();
+        assert_eq!(&bytes[..10], &[STX, 28, 0, 0, 1, 1, 200, 30, 0, 0]);
+        assert_eq!(&bytes[10..14], &1000u32.to_le_bytes());
+        let yawspeed = f32::from_le_bytes(bytes[34..38].try_into().unwrap());
+        assert!((yawspeed - PI).abs() < 1e-6);
+        assert_eq!(encoder.sequence(), 2);
+        let (header, parsed) = parse::<Attitude>(bytes).unwrap();
+        assert_eq!(header.sequence, 1);
+        assert_eq!(header.length, bytes.len());
+        assert_eq!(parsed, attitude);
+    }
+
+    #[test]
+    fn payload_is_truncated() {
+        let mut encoder = MavlinkEncoder::new(1, 1);
+        let imu = ScaledImu {
+            time_boot_ms: 5,
+            acceleration: [0, 0, 1000],
+            ..Default::default()
+        };
+
+        // Act
+        let frame = encoder.encode(&imu);
+
+        assert_eq!(frame.as_bytes()[1], 10);
+        assert_eq!(frame.as_bytes().len(), 22);
+        assert_eq!(parse::<ScaledImu>(frame.as_bytes()).unwrap().1, imu);
+    }
+
+    #[test]
+    fn highres_imu_is_parsed_and_corruption_rejected() {
+        let mut encoder = MavlinkEncoder::new(1, 1);
+        let imu = HighresImu {
+            time_usec: 123_456_789,
+            acceleration: Vector::new(0.0, 0.0, -STANDARD_GRAVITY),
+            angular_rate: Vector::new(PI, 0.0, 0.0),
+            magnetic_field: Vector::new(0.2, 0.0, 0.4),
+            fields_updated: 0x1ff,
+            ..Default::default()
+        };
+        let frame = encoder.encode(&imu);
+        let mut corrupt = frame;
+        corrupt.buffer[12] ^= 0x01;
+
+        // Act
+        let (_, parsed) = parse::<HighresImu>(frame.as_bytes()).unwrap();
+
+        assert_eq!(parsed, imu);
+        assert!((parsed.gyroscope().x - 180.0).abs() < 1e-3);
+        assert!((parsed.accelerometer().z + 1.0).abs() < 1e-6);
+        assert!((parsed.magnetometer().z - 40.0).abs() < 1e-4);
+        assert_eq!(
+            parse::<HighresImu>(corrupt.as_bytes()),
+            Err(MavlinkError::Crc)
+        );
+        assert_eq!(
+            parse::<Attitude>(frame.as_bytes()),
+            Err(MavlinkError::UnexpectedMessage(105))
+        );
+        assert_eq!(
+            parse::<HighresImu>(&frame.as_bytes()[..20]),
+            Err(MavlinkError::Truncated)
+        );
+    }
+}
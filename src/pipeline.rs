@@ -0,0 +1,330 @@
+use crate::{
+    axes_swap, Ahrs, AxesAlignment, FusionAhrs, FusionOffset, InertialCalibration,
+    MagneticCalibration, Quaternion, RawScaling, Settings, Vector,
+};
+
+/// Configuration of every stage of a [`Pipeline`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PipelineConfig {
+    /// Sample rate in Hz used by the gyroscope offset correction.
+    pub sample_rate: u32,
+    /// Gyroscope scaling to degrees per second. Unless the scaling is the
+    /// default, the full-scale range is used as the gyroscope range of the
+    /// AHRS algorithm settings if that range is not set.
+    pub gyroscope_scaling: RawScaling,
+    /// Accelerometer scaling to g.
+    pub accelerometer_scaling: RawScaling,
+    /// Magnetometer scaling to arbitrary units.
+    pub magnetometer_scaling: RawScaling,
+    /// Alignment of the sensor axes relative to the body axes.
+    pub alignment: AxesAlignment,
+    /// Gyroscope calibration.
+    pub gyroscope_calibration: InertialCalibration,
+    /// Accelerometer calibration.
+    pub accelerometer_calibration: InertialCalibration,
+    /// Magnetometer calibration.
+    pub magnetometer_calibration: MagneticCalibration,
+    /// Whether the gyroscope offset is corrected at runtime by
+    /// [`FusionOffset`].
+    pub offset_correction: bool,
+    /// AHRS algorithm settings.
+    pub settings: Settings,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 100,
+            gyroscope_scaling: RawScaling::default(),
+            accelerometer_scaling: RawScaling::default(),
+            magnetometer_scaling: RawScaling::default(),
+            alignment: AxesAlignment::default(),
+            gyroscope_calibration: InertialCalibration::default(),
+            accelerometer_calibration: InertialCalibration::default(),
+            magnetometer_calibration: MagneticCalibration::default(),
+            offset_correction: true,
+            settings: Settings::default(),
+        }
+    }
+}
+
+/// Raw sensor measurements in counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct RawSample {
+    /// Gyroscope measurement.
+    pub gyroscope: [i32; 3],
+    /// Accelerometer measurement.
+    pub accelerometer: [i32; 3],
+    /// Magnetometer measurement, if available.
+    pub magnetometer: Option<[i32; 3]>,
+}
+
+/// Sensor measurements at one stage of a [`Pipeline`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SensorValues {
+    /// Gyroscope measurement in degrees per second.
+    pub gyroscope: Vector,
+    /// Accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Magnetometer measurement in arbitrary units, if available.
+    pub magnetometer: Option<Vector>,
+}
+
+/// Saturation of the raw sensor measurements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Saturation {
+    /// Gyroscope saturated.
+    pub gyroscope: bool,
+    /// Accelerometer saturated.
+    pub accelerometer: bool,
+    /// Magnetometer saturated.
+    pub magnetometer: bool,
+}
+
+impl Saturation {
+    /// Returns true if any sensor is saturated.
+    pub fn any(&self) -> bool {
+        self.gyroscope || self.accelerometer || self.magnetometer
+    }
+}
+
+/// Output of [`Pipeline::process`] including the intermediate values of each
+/// stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct PipelineOutput {
+    /// Saturation of the raw measurements.
+    pub saturation: Saturation,
+    /// Measurements scaled to physical units.
+    pub scaled: SensorValues,
+    /// Scaled measurements in the body axes.
+    pub aligned: SensorValues,
+    /// Aligned measurements after calibration.
+    pub calibrated: SensorValues,
+    /// Calibrated gyroscope measurement after offset correction.
+    pub gyroscope: Vector,
+    /// Quaternion describing the sensor relative to the Earth.
+    pub quaternion: Quaternion,
+}
+
+/// Sensor processing pipeline from raw counts to orientation.
+///
+/// Each call to [`process`](Pipeline::process) performs unit scaling, axes
+/// alignment, calibration, gyroscope offset correction, and the AHRS update.
+pub struct Pipeline<A = FusionAhrs> {
+    config: PipelineConfig,
+    offset: FusionOffset,
+    ahrs: A,
+}
+
+impl Pipeline {
+    /// Create a new `Pipeline` instance.
+    pub fn new(config: PipelineConfig) -> Self {
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(ahrs_settings(&config));
+        Self {
+            config,
+            offset: FusionOffset::new(config.sample_rate),
+            ahrs,
+        }
+    }
+}
+
+/// Returns the AHRS algorithm settings of the configuration with the
+/// gyroscope range derived from the gyroscope scaling if it is not set.
+fn ahrs_settings(config: &PipelineConfig) -> Settings {
+    let mut settings = config.settings;
+    if settings.gyroscope_range() == 0.0 && config.gyroscope_scaling != RawScaling::default() {
+        settings.set_gyroscope_range(config.gyroscope_scaling.full_scale());
+    }
+    settings
+}
+
+impl<A: Ahrs> Pipeline<A> {
+    /// Create a new `Pipeline` instance with another AHRS algorithm. The
+    /// AHRS algorithm settings of the configuration are not used.
+    pub fn with_ahrs(config: PipelineConfig, ahrs: A) -> Self {
+        Self {
+            config,
+            offset: FusionOffset::new(config.sample_rate),
+            ahrs,
+        }
+    }
+
+    /// Returns the pipeline configuration.
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// Returns the AHRS algorithm.
+    pub fn ahrs(&self) -> &A {
+        &self.ahrs
+    }
+
+    /// Returns the AHRS algorithm mutably, for example to set the heading.
+    pub fn ahrs_mut(&mut self) -> &mut A {
+        &mut self.ahrs
+    }
+
+    /// Resets the gyroscope offset correction and the AHRS algorithm.
+    pub fn reset(&mut self) {
+        self.offset = FusionOffset::new(self.config.sample_rate);
+        self.ahrs.reset();
+    }
+
+    /// Processes raw measurements and updates the AHRS algorithm.
+    ///
+    /// The magnetometer is used if it is present in the sample.
+    ///
+    /// Arguments:
+    /// - `raw`: Raw measurements.
+    /// - `delta_time`: Delta time in seconds.
+    pub fn process(&mut self, raw: RawSample, delta_time: f32) -> PipelineOutput {
+        let config = &self.config;
+        let saturation = Saturation {
+            gyroscope: config.gyroscope_scaling.is_saturated(raw.gyroscope),
+            accelerometer: config.accelerometer_scaling.is_saturated(raw.accelerometer),
+            magnetometer: raw
+                .magnetometer
+                .is_some_and(|magnetometer| config.magnetometer_scaling.is_saturated(magnetometer)),
+        };
+        let scaled = SensorValues {
+            gyroscope: config.gyroscope_scaling.apply(raw.gyroscope),
+            accelerometer: config.accelerometer_scaling.apply(raw.accelerometer),
+            magnetometer: raw
+                .magnetometer
+                .map(|magnetometer| config.magnetometer_scaling.apply(magnetometer)),
+        };
+        let aligned = SensorValues {
+            gyroscope: axes_swap(scaled.gyroscope, config.alignment),
+            accelerometer: axes_swap(scaled.accelerometer, config.alignment),
+            magnetometer: scaled
+                .magnetometer
+                .map(|magnetometer| axes_swap(magnetometer, config.alignment)),
+        };
+        let calibrated = SensorValues {
+            gyroscope: config.gyroscope_calibration.apply(aligned.gyroscope),
+            accelerometer: config
+                .accelerometer_calibration
+                .apply(aligned.accelerometer),
+            magnetometer: aligned
+                .magnetometer
+                .map(|magnetometer| config.magnetometer_calibration.apply(magnetometer)),
+        };
+        let gyroscope = if config.offset_correction {
+            self.offset.update(calibrated.gyroscope)
+        } else {
+            calibrated.gyroscope
+        };
+
+        match calibrated.magnetometer {
+            Some(magnetometer) => self.ahrs.update(
+                gyroscope,
+                calibrated.accelerometer,
+                magnetometer,
+                delta_time,
+            ),
+            None => {
+                self.ahrs
+                    .update_no_magnetometer(gyroscope, calibrated.accelerometer, delta_time)
+            }
+        }
+
+        PipelineOutput {
+            saturation,
+            scaled,
+            aligned,
+            calibrated,
+            gyroscope,
+            quaternion: self.ahrs.quaternion(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GyroscopeRange;
+
+    #[test]
+    fn stages_are_applied_in_order() {
+        let config = PipelineConfig {
+            gyroscope_scaling: RawScaling {
+                scale: 0.5,
+                ..Default::default()
+            },
+            accelerometer_scaling: RawScaling::from_sensitivity(1000.0),
+            alignment: AxesAlignment::PyNxPz,
+            gyroscope_calibration: InertialCalibration {
+                offset: Vector::new(1.0, 0.0, 0.0),
+                ..Default::default()
+            },
+            offset_correction: false,
+            ..Default::default()
+        };
+        let mut pipeline = Pipeline::new(config);
+        let raw = RawSample {
+            gyroscope: [4, 2, 0],
+            accelerometer: [0, 0, 1000],
+            magnetometer: None,
+        };
+
+        // Act
+        let output = pipeline.process(raw, 0.01);
+
+        assert_eq!(output.scaled.gyroscope, Vector::new(2.0, 1.0, 0.0));
+        assert_eq!(output.aligned.gyroscope, Vector::new(1.0, -2.0, 0.0));
+        assert_eq!(output.calibrated.gyroscope, Vector::new(0.0, -2.0, 0.0));
+        assert_eq!(output.gyroscope, output.calibrated.gyroscope);
+        assert_eq!(output.calibrated.accelerometer, Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn gyroscope_range_is_derived_only_if_unset() {
+        let mut settings = Settings::new();
+        settings.set_gyroscope_range(500.0);
+        let configured = PipelineConfig {
+            gyroscope_scaling: GyroscopeRange::Dps2000.into(),
+            settings,
+            ..Default::default()
+        };
+        let derived = PipelineConfig {
+            gyroscope_scaling: GyroscopeRange::Dps2000.into(),
+            ..Default::default()
+        };
+
+        // Act
+        let settings =
+            [configured, derived, PipelineConfig::default()].map(|config| ahrs_settings(&config));
+
+        let ranges = settings.map(|settings| settings.gyroscope_range());
+        assert_eq!(ranges, [500.0, 2000.0, 0.0]);
+    }
+
+    #[test]
+    fn stationary_sensor_stays_level() {
+        let mut pipeline = Pipeline::new(PipelineConfig::default());
+        let raw = RawSample {
+            gyroscope: [0, 0, 0],
+            accelerometer: [0, 0, 1],
+            magnetometer: Some([1, 0, 0]),
+        };
+
+        // Act
+        for _ in 0..100 {
+            pipeline.process(raw, 0.01);
+        }
+
+        let quaternion = pipeline.ahrs().get_quaternion();
+        assert!(quaternion.angle_to(Quaternion::IDENTITY) < 0.1);
+    }
+}
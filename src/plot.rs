@@ -0,0 +1,441 @@
+//! SVG time-series charts of sensor measurements and AHRS algorithm outputs.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+use std::{fs, io};
+
+use crate::{Ahrs, Vector};
+
+/// Red line colour.
+pub const RED: &str = "#d62728";
+/// Green line colour.
+pub const GREEN: &str = "#2ca02c";
+/// Blue line colour.
+pub const BLUE: &str = "#1f77b4";
+/// Orange line colour.
+pub const ORANGE: &str = "#ff7f0e";
+/// Purple line colour.
+pub const PURPLE: &str = "#9467bd";
+
+const WIDTH: f32 = 1000.0;
+const CHART_HEIGHT: f32 = 200.0;
+const MARGIN_LEFT: f32 = 70.0;
+const MARGIN_RIGHT: f32 = 170.0;
+const MARGIN_TOP: f32 = 30.0;
+const MARGIN_BOTTOM: f32 = 30.0;
+
+/// Maximum number of points drawn per line. Longer series are reduced to the
+/// minimum and maximum of each bucket so that spikes remain visible.
+const MAXIMUM_POINTS: usize = 4000;
+
+/// Line of a [`Chart`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    /// Legend label.
+    pub label: String,
+    /// SVG colour.
+    pub color: String,
+    /// Values, one per time stamp of the [`Plot`].
+    pub values: Vec<f32>,
+}
+
+/// Chart with a shared time axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    /// Chart title.
+    pub title: String,
+    /// Y-axis label.
+    pub unit: String,
+    /// Lines of the chart.
+    pub series: Vec<Series>,
+}
+
+impl Chart {
+    /// Create a new `Chart` instance without lines.
+    pub fn new(title: &str, unit: &str) -> Self {
+        Self {
+            title: title.into(),
+            unit: unit.into(),
+            series: Vec::new(),
+        }
+    }
+
+    /// Adds a line to the chart.
+    pub fn with_series(mut self, label: &str, color: &str, values: Vec<f32>) -> Self {
+        self.series.push(Series {
+            label: label.into(),
+            color: color.into(),
+            values,
+        });
+        self
+    }
+}
+
+/// Vertically stacked charts sharing a time axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plot {
+    /// Time stamps in seconds.
+    pub time: Vec<f32>,
+    /// Charts from top to bottom.
+    pub charts: Vec<Chart>,
+}
+
+impl Plot {
+    /// Create a new `Plot` instance without charts.
+    pub fn new(time: Vec<f32>) -> Self {
+        Self {
+            time,
+            charts: Vec::new(),
+        }
+    }
+
+    /// Adds a chart below the existing charts.
+    pub fn with_chart(mut self, chart: Chart) -> Self {
+        self.charts.push(chart);
+        self
+    }
+
+    /// Renders the plot as an SVG document.
+    pub fn to_svg(&self) -> String {
+        let height = self.charts.len() as f32 * (CHART_HEIGHT + MARGIN_TOP + MARGIN_BOTTOM);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif" font-size="11">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{WIDTH}" height="{height}" fill="white"/>"#
+        );
+        let time_range = range(self.time.iter().copied());
+        for (index, chart) in self.charts.iter().enumerate() {
+            let top = index as f32 * (CHART_HEIGHT + MARGIN_TOP + MARGIN_BOTTOM) + MARGIN_TOP;
+            self.render_chart(&mut svg, chart, top, time_range);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Writes the plot to an SVG file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    fn render_chart(&self, svg: &mut String, chart: &Chart, top: f32, time_range: (f32, f32)) {
+        let left = MARGIN_LEFT;
+        let right = WIDTH - MARGIN_RIGHT;
+        let bottom = top + CHART_HEIGHT;
+        let values = chart
+            .series
+            .iter()
+            .flat_map(|series| series.values.iter().copied());
+        let (minimum, maximum) = range(values);
+        let x = |time: f32| {
+            left + (time - time_range.0) / (time_range.1 - time_range.0) * (right - left)
+        };
+        let y = |value: f32| bottom - (value - minimum) / (maximum - minimum) * CHART_HEIGHT;
+
+        let _ = writeln!(
+            svg,
+            r#"<text x="{left}" y="{}" font-size="13" font-weight="bold">{}</text>"#,
+            top - 8.0,
+            escape(&chart.title)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text transform="translate({},{}) rotate(-90)" text-anchor="middle">{}</text>"#,
+            left - 50.0,
+            top + CHART_HEIGHT / 2.0,
+            escape(&chart.unit)
+        );
+
+        // Grid and tick labels
+        for tick in ticks(minimum, maximum) {
+            let position = y(tick);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{left}" y1="{position:.1}" x2="{right}" y2="{position:.1}" stroke="#e0e0e0"/><text x="{}" y="{:.1}" text-anchor="end">{}</text>"##,
+                left - 5.0,
+                position + 4.0,
+                format_tick(tick)
+            );
+        }
+        for tick in ticks(time_range.0, time_range.1) {
+            let position = x(tick);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{position:.1}" y1="{top}" x2="{position:.1}" y2="{bottom}" stroke="#e0e0e0"/><text x="{position:.1}" y="{}" text-anchor="middle">{}</text>"##,
+                bottom + 15.0,
+                format_tick(tick)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{left}" y="{top}" width="{}" height="{CHART_HEIGHT}" fill="none" stroke="black"/>"#,
+            right - left
+        );
+
+        // Lines and legend
+        for (index, series) in chart.series.iter().enumerate() {
+            let mut points = String::new();
+            for (time, value) in decimate(&self.time, &series.values) {
+                let _ = write!(points, "{:.1},{:.1} ", x(time), y(value));
+            }
+            let _ = writeln!(
+                svg,
+                r#"<polyline fill="none" stroke="{}" stroke-width="1" points="{}"/>"#,
+                escape(&series.color),
+                points.trim_end()
+            );
+            let legend = top + 10.0 + index as f32 * 16.0;
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{legend}" x2="{}" y2="{legend}" stroke="{}" stroke-width="2"/><text x="{}" y="{}">{}</text>"#,
+                right + 10.0,
+                right + 30.0,
+                escape(&series.color),
+                right + 35.0,
+                legend + 4.0,
+                escape(&series.label)
+            );
+        }
+    }
+}
+
+/// Returns the finite minimum and maximum of the values, widened if they are
+/// equal.
+fn range(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    let (minimum, maximum) = values.filter(|value| value.is_finite()).fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(minimum, maximum), value| (minimum.min(value), maximum.max(value)),
+    );
+    if minimum > maximum {
+        (0.0, 1.0)
+    } else if minimum == maximum {
+        (minimum - 0.5, maximum + 0.5)
+    } else {
+        (minimum, maximum)
+    }
+}
+
+/// Returns round tick values between the minimum and maximum.
+fn ticks(minimum: f32, maximum: f32) -> Vec<f32> {
+    let rough = (maximum - minimum) / 5.0;
+    let magnitude = 10_f32.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (minimum / step).ceil() as i64;
+    let last = (maximum / step).floor() as i64;
+    (first..=last).map(|index| index as f32 * step).collect()
+}
+
+fn format_tick(value: f32) -> String {
+    let text = std::format!("{value:.3}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".into(),
+        _ => text.into(),
+    }
+}
+
+/// Returns the points to draw, reduced to the minimum and maximum of each
+/// bucket if there are more than [`MAXIMUM_POINTS`].
+fn decimate(time: &[f32], values: &[f32]) -> Vec<(f32, f32)> {
+    let points = time
+        .iter()
+        .copied()
+        .zip(values.iter().copied())
+        .filter(|(_, value)| value.is_finite());
+    let count = time.len().min(values.len());
+    if count <= MAXIMUM_POINTS {
+        return points.collect();
+    }
+    let points: Vec<_> = points.collect();
+    let size = points.len().div_ceil(MAXIMUM_POINTS / 2);
+    let mut result = Vec::with_capacity(MAXIMUM_POINTS);
+    for bucket in points.chunks(size) {
+        let minimum = bucket.iter().min_by(|a, b| a.1.total_cmp(&b.1));
+        let maximum = bucket.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+        if let (Some(&minimum), Some(&maximum)) = (minimum, maximum) {
+            if minimum.0 <= maximum.0 {
+                result.extend([minimum, maximum]);
+            } else {
+                result.extend([maximum, minimum]);
+            }
+        }
+    }
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Recorder of measurements and AHRS algorithm outputs for plotting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recorder {
+    time: Vec<f32>,
+    gyroscope: [Vec<f32>; 3],
+    accelerometer: [Vec<f32>; 3],
+    euler: [Vec<f32>; 3],
+    flags: [Vec<f32>; 4],
+    errors: [Vec<f32>; 2],
+}
+
+impl Recorder {
+    /// Create a new `Recorder` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the measurements and the current outputs of the AHRS
+    /// algorithm.
+    ///
+    /// Arguments:
+    /// - `time`: Time in seconds.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    /// - `ahrs`: AHRS algorithm.
+    pub fn record(
+        &mut self,
+        time: f32,
+        gyroscope: Vector,
+        accelerometer: Vector,
+        ahrs: &(impl Ahrs + ?Sized),
+    ) {
+        let euler = ahrs.quaternion().to_euler();
+        let flags = ahrs.flags();
+        let states = ahrs.internal_states();
+        self.time.push(time);
+        push(&mut self.gyroscope, [gyroscope.x, gyroscope.y, gyroscope.z]);
+        push(
+            &mut self.accelerometer,
+            [accelerometer.x, accelerometer.y, accelerometer.z],
+        );
+        push(&mut self.euler, [euler.roll, euler.pitch, euler.yaw]);
+        push(
+            &mut self.flags,
+            [
+                flags.initialising(),
+                flags.angular_rate_recovery(),
+                flags.acceleration_recovery(),
+                flags.magnetic_recovery(),
+            ]
+            .map(|flag| flag as u8 as f32),
+        );
+        push(
+            &mut self.errors,
+            [states.acceleration_error(), states.magnetic_error()],
+        );
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    /// Returns true if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Returns a plot of the gyroscope, accelerometer, Euler angles, flags,
+    /// and internal state errors.
+    pub fn plot(&self) -> Plot {
+        let xyz = |title: &str, unit: &str, values: &[Vec<f32>; 3], labels: [&str; 3]| {
+            Chart::new(title, unit)
+                .with_series(labels[0], RED, values[0].clone())
+                .with_series(labels[1], GREEN, values[1].clone())
+                .with_series(labels[2], BLUE, values[2].clone())
+        };
+        Plot::new(self.time.clone())
+            .with_chart(xyz(
+                "Gyroscope",
+                "Degrees/s",
+                &self.gyroscope,
+                ["X", "Y", "Z"],
+            ))
+            .with_chart(xyz(
+                "Accelerometer",
+                "g",
+                &self.accelerometer,
+                ["X", "Y", "Z"],
+            ))
+            .with_chart(xyz(
+                "Euler angles",
+                "Degrees",
+                &self.euler,
+                ["Roll", "Pitch", "Yaw"],
+            ))
+            .with_chart(
+                Chart::new("Flags", "State")
+                    .with_series("Initialising", RED, self.flags[0].clone())
+                    .with_series("Angular rate recovery", GREEN, self.flags[1].clone())
+                    .with_series("Acceleration recovery", BLUE, self.flags[2].clone())
+                    .with_series("Magnetic recovery", ORANGE, self.flags[3].clone()),
+            )
+            .with_chart(
+                Chart::new("Internal states", "Degrees")
+                    .with_series("Acceleration error", RED, self.errors[0].clone())
+                    .with_series("Magnetic error", PURPLE, self.errors[1].clone()),
+            )
+    }
+}
+
+fn push<const N: usize>(columns: &mut [Vec<f32>; N], values: [f32; N]) {
+    for (column, value) in columns.iter_mut().zip(values) {
+        column.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FusionAhrs;
+
+    #[test]
+    fn ticks_are_round() {
+        // Act
+        let ticks = ticks(-0.3, 1.7);
+
+        assert_eq!(ticks.len(), 4);
+        assert_eq!(format_tick(ticks[0]), "0");
+        assert_eq!(format_tick(ticks[1]), "0.5");
+    }
+
+    #[test]
+    fn recorder_plots_every_chart() {
+        let mut recorder = Recorder::new();
+        let mut ahrs = FusionAhrs::new();
+        for index in 0..10_000 {
+            let accelerometer = Vector::new(0.0, 0.0, 1.0);
+            ahrs.update_no_magnetometer(Vector::ZERO, accelerometer, 0.01);
+            recorder.record(index as f32 * 0.01, Vector::ZERO, accelerometer, &ahrs);
+        }
+
+        // Act
+        let svg = recorder.plot().to_svg();
+
+        assert_eq!(recorder.len(), 10_000);
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<polyline").count(), 15);
+        let points = svg
+            .split("points=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap()
+            .split(' ')
+            .count();
+        assert!(points <= MAXIMUM_POINTS);
+    }
+}
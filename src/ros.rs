@@ -0,0 +1,267 @@
+//! ROS 2 `sensor_msgs/msg/Imu` serialisation.
+//!
+//! Messages are encoded as little-endian CDR, including the encapsulation
+//! header, as published by ROS 2 middleware. ROS uses the East-North-Up
+//! convention (REP-103), so the AHRS algorithm should be configured with
+//! [`Convention::EastNorthUp`](crate::Convention).
+
+use crate::{Ahrs, BufferFull, Matrix, Vector};
+
+const ENCAPSULATION_HEADER: [u8; 4] = [0x00, 0x01, 0x00, 0x00];
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// ROS time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Time {
+    /// Seconds.
+    pub sec: i32,
+    /// Nanoseconds within the second.
+    pub nanosec: u32,
+}
+
+impl Time {
+    /// Latest time that can be represented.
+    pub const MAX: Time = Time {
+        sec: i32::MAX,
+        nanosec: 999_999_999,
+    };
+
+    /// Create a new `Time` instance from nanoseconds. Times beyond the range
+    /// of the seconds field, such as after 2038 for Unix time, saturate to
+    /// [`Time::MAX`].
+    pub fn from_nanoseconds(nanoseconds: u64) -> Self {
+        match i32::try_from(nanoseconds / 1_000_000_000) {
+            Ok(sec) => Self {
+                sec,
+                nanosec: (nanoseconds % 1_000_000_000) as u32,
+            },
+            Err(_) => Self::MAX,
+        }
+    }
+}
+
+/// `sensor_msgs/msg/Imu` message in ROS units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Imu<'a> {
+    /// Header time stamp.
+    pub stamp: Time,
+    /// Header frame ID.
+    pub frame_id: &'a str,
+    /// Orientation quaternion in x, y, z, w order.
+    pub orientation: [f64; 4],
+    /// Orientation covariance in row-major order about the x, y, and z axes.
+    pub orientation_covariance: [f64; 9],
+    /// Angular velocity in radians per second.
+    pub angular_velocity: [f64; 3],
+    /// Angular velocity covariance in row-major order.
+    pub angular_velocity_covariance: [f64; 9],
+    /// Linear acceleration in metres per second squared.
+    pub linear_acceleration: [f64; 3],
+    /// Linear acceleration covariance in row-major order.
+    pub linear_acceleration_covariance: [f64; 9],
+}
+
+impl<'a> Imu<'a> {
+    /// Create a new `Imu` instance from the AHRS algorithm output. The
+    /// covariances are zero, meaning unknown.
+    ///
+    /// Arguments:
+    /// - `stamp`: Time stamp.
+    /// - `frame_id`: Frame ID of the sensor.
+    /// - `ahrs`: AHRS algorithm.
+    /// - `gyroscope`: Gyroscope measurement in degrees per second.
+    /// - `accelerometer`: Accelerometer measurement in g.
+    pub fn from_ahrs(
+        stamp: Time,
+        frame_id: &'a str,
+        ahrs: &(impl Ahrs + ?Sized),
+        gyroscope: Vector,
+        accelerometer: Vector,
+    ) -> Self {
+        let quaternion = ahrs.quaternion();
+        let vector = |vector: Vector, scale: f64| {
+            [
+                vector.x as f64 * scale,
+                vector.y as f64 * scale,
+                vector.z as f64 * scale,
+            ]
+        };
+        Self {
+            stamp,
+            frame_id,
+            orientation: [
+                quaternion.x as f64,
+                quaternion.y as f64,
+                quaternion.z as f64,
+                quaternion.w as f64,
+            ],
+            orientation_covariance: [0.0; 9],
+            angular_velocity: vector(gyroscope, core::f64::consts::PI / 180.0),
+            angular_velocity_covariance: [0.0; 9],
+            linear_acceleration: vector(accelerometer, STANDARD_GRAVITY),
+            linear_acceleration_covariance: [0.0; 9],
+        }
+    }
+
+    /// Sets the orientation covariance in radians squared about the Earth
+    /// axes, for example from [`Eskf::attitude_covariance`](crate::Eskf::attitude_covariance).
+    pub fn with_orientation_covariance(mut self, covariance: Matrix) -> Self {
+        let [x, y, z] = covariance.rows();
+        self.orientation_covariance = [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z].map(f64::from);
+        self
+    }
+
+    /// Returns the length of the encoded message in bytes.
+    pub fn encoded_len(&self) -> usize {
+        // Header, string length, and string with terminator, then doubles
+        // aligned to 8 bytes.
+        let header = (8 + 4 + self.frame_id.len() + 1).next_multiple_of(8);
+        ENCAPSULATION_HEADER.len() + header + 8 * (4 + 9 + 3 + 9 + 3 + 9)
+    }
+
+    /// Encodes the message into the buffer as CDR. Returns the number of
+    /// bytes written.
+    pub fn encode(&self, buffer: &mut [u8]) -> Result<usize, BufferFull> {
+        let length = self.encoded_len();
+        let buffer = buffer.get_mut(..length).ok_or(BufferFull)?;
+        buffer[..4].copy_from_slice(&ENCAPSULATION_HEADER);
+        let mut writer = Writer {
+            buffer: &mut buffer[4..],
+            position: 0,
+        };
+        writer.bytes(&self.stamp.sec.to_le_bytes());
+        writer.bytes(&self.stamp.nanosec.to_le_bytes());
+        writer.bytes(&(self.frame_id.len() as u32 + 1).to_le_bytes());
+        writer.bytes(self.frame_id.as_bytes());
+        writer.bytes(&[0]);
+        for values in [
+            &self.orientation[..],
+            &self.orientation_covariance,
+            &self.angular_velocity,
+            &self.angular_velocity_covariance,
+            &self.linear_acceleration,
+            &self.linear_acceleration_covariance,
+        ] {
+            for &value in values {
+                writer.f64(value);
+            }
+        }
+        Ok(length)
+    }
+}
+
+/// CDR writer. Positions are relative to the end of the encapsulation header.
+struct Writer<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+
+    fn f64(&mut self, value: f64) {
+        let aligned = self.position.next_multiple_of(8);
+        self.buffer[self.position..aligned].fill(0);
+        self.position = aligned;
+        self.bytes(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FusionAhrs;
+
+    #[test]
+    fn time_saturates_beyond_seconds_range() {
+        let last = i32::MAX as u64 * 1_000_000_000 + 999_999_999;
+
+        // Act
+        let times = [last, last + 1, u64::MAX].map(Time::from_nanoseconds);
+
+        assert_eq!(times, [Time::MAX; 3]);
+    }
+
+    #[test]
+    fn level_sensor_golden_bytes() {
+        let ahrs = FusionAhrs::new();
+        let imu = Imu::from_ahrs(
+            Time::from_nanoseconds(1_000_000_002),
+            "imu",
+            &ahrs,
+            Vector::ZERO,
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        let mut expected = [0; 316];
+        expected[..16].copy_from_slice(&[
+            0x00, 0x01, 0x00, 0x00, // encapsulation
+            0x01, 0x00, 0x00, 0x00, // sec
+            0x02, 0x00, 0x00, 0x00, // nanosec
+            0x04, 0x00, 0x00, 0x00, // frame_id length
+        ]);
+        expected[16..20].copy_from_slice(b"imu\0");
+        expected[44..52].copy_from_slice(&[0, 0, 0, 0, 0, 0, 0xf0, 0x3f]); // w = 1
+        expected[236..244].copy_from_slice(&[0x05, 0xa3, 0x92, 0x3a, 0x01, 0x9d, 0x23, 0x40]); // z = 9.80665
+        let mut buffer = [0xff; 400];
+
+        // Act
+        let length = imu.encode(&mut buffer).unwrap();
+
+        assert_eq!(length, 316);
+        assert_eq!(buffer[..length], expected);
+    }
+
+    #[test]
+    fn frame_id_is_padded_and_units_converted() {
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_quaternion(crate::Quaternion::new(0.5, -0.5, 0.25, 0.75));
+        let imu = Imu::from_ahrs(
+            Time::default(),
+            "base_link",
+            &ahrs,
+            Vector::new(180.0, 0.0, 0.0),
+            Vector::ZERO,
+        );
+        let mut buffer = [0xff; 400];
+
+        // Act
+        let length = imu.encode(&mut buffer).unwrap();
+
+        assert_eq!(length, 4 + 24 + 8 * 37);
+        assert_eq!(&buffer[4 + 12..4 + 24], b"base_link\0\0\0");
+        assert_eq!(imu.orientation, [-0.5, 0.25, 0.75, 0.5]);
+        let angular_velocity = f64::from_le_bytes(buffer[4 + 128..4 + 136].try_into().unwrap());
+        assert!((angular_velocity - core::f64::consts::PI).abs() < 1e-6);
+        assert_eq!(imu.encode(&mut buffer[..length - 1]), Err(BufferFull));
+    }
+
+    #[test]
+    fn orientation_covariance_is_row_major() {
+        let covariance = Matrix::from_rows(
+            Vector::new(1.0, 2.0, 3.0),
+            Vector::new(2.0, 4.0, 5.0),
+            Vector::new(3.0, 5.0, 6.0),
+        );
+        let imu = Imu::from_ahrs(
+            Time::default(),
+            "imu",
+            &crate::Eskf::default(),
+            Vector::ZERO,
+            Vector::ZERO,
+        );
+
+        // Act
+        let imu = imu.with_orientation_covariance(covariance);
+
+        assert_eq!(
+            imu.orientation_covariance,
+            [1.0, 2.0, 3.0, 2.0, 4.0, 5.0, 3.0, 5.0, 6.0]
+        );
+    }
+}
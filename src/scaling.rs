@@ -0,0 +1,212 @@
+use crate::Vector;
+
+/// Conversion of raw sensor counts to physical units.
+///
+/// The default value passes counts through unscaled and only saturates at
+/// the limits of `i32`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct RawScaling {
+    /// Physical units per LSB.
+    pub scale: f32,
+    /// Most negative count the sensor can output.
+    pub minimum: i32,
+    /// Most positive count the sensor can output.
+    pub maximum: i32,
+}
+
+impl Default for RawScaling {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            minimum: i32::MIN,
+            maximum: i32::MAX,
+        }
+    }
+}
+
+impl RawScaling {
+    /// Create a new `RawScaling` instance from a full-scale range.
+    ///
+    /// Arguments:
+    /// - `full_scale`: Full-scale range in physical units, e.g. 2000 for a
+    ///   ±2000 dps gyroscope.
+    /// - `bits`: Resolution of the signed sensor output, e.g. 16.
+    ///
+    /// Panics if `bits` is not in the range 1 to 32.
+    pub fn from_full_scale(full_scale: f32, bits: u32) -> Self {
+        assert!((1..=32).contains(&bits), "resolution must be 1 to 32 bits");
+        let counts = 1_i64 << (bits - 1);
+        Self {
+            scale: full_scale / counts as f32,
+            minimum: -counts as i32,
+            maximum: (counts - 1) as i32,
+        }
+    }
+
+    /// Create a new `RawScaling` instance for a 16-bit sensor from its
+    /// sensitivity in LSB per physical unit.
+    pub fn from_sensitivity(lsb_per_unit: f32) -> Self {
+        Self {
+            scale: 1.0 / lsb_per_unit,
+            minimum: i16::MIN as i32,
+            maximum: i16::MAX as i32,
+        }
+    }
+
+    /// Returns the full-scale range in physical units.
+    ///
+    /// For a gyroscope, this is the value to pass to
+    /// [`Settings::set_gyroscope_range`](crate::Settings::set_gyroscope_range).
+    pub fn full_scale(&self) -> f32 {
+        self.scale * -(self.minimum as f32)
+    }
+
+    /// Converts raw counts to physical units.
+    pub fn apply<T: Into<i32> + Copy>(&self, raw: [T; 3]) -> Vector {
+        Vector::new(
+            raw[0].into() as f32 * self.scale,
+            raw[1].into() as f32 * self.scale,
+            raw[2].into() as f32 * self.scale,
+        )
+    }
+
+    /// Returns true if any axis is at the limit of the sensor output.
+    pub fn is_saturated<T: Into<i32> + Copy>(&self, raw: [T; 3]) -> bool {
+        raw.iter().any(|&count| {
+            let count = count.into();
+            count <= self.minimum || count >= self.maximum
+        })
+    }
+}
+
+/// Common gyroscope full-scale ranges of 16-bit sensors.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum GyroscopeRange {
+    Dps250,
+    Dps500,
+    Dps1000,
+    #[default]
+    Dps2000,
+}
+
+impl GyroscopeRange {
+    /// Returns the full-scale range in degrees per second.
+    pub fn degrees_per_second(self) -> f32 {
+        match self {
+            GyroscopeRange::Dps250 => 250.0,
+            GyroscopeRange::Dps500 => 500.0,
+            GyroscopeRange::Dps1000 => 1000.0,
+            GyroscopeRange::Dps2000 => 2000.0,
+        }
+    }
+}
+
+impl From<GyroscopeRange> for RawScaling {
+    fn from(range: GyroscopeRange) -> Self {
+        RawScaling::from_full_scale(range.degrees_per_second(), 16)
+    }
+}
+
+/// Common accelerometer full-scale ranges of 16-bit sensors.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum AccelerometerRange {
+    G2,
+    G4,
+    G8,
+    #[default]
+    G16,
+}
+
+impl AccelerometerRange {
+    /// Returns the full-scale range in g.
+    pub fn g(self) -> f32 {
+        match self {
+            AccelerometerRange::G2 => 2.0,
+            AccelerometerRange::G4 => 4.0,
+            AccelerometerRange::G8 => 8.0,
+            AccelerometerRange::G16 => 16.0,
+        }
+    }
+}
+
+impl From<AccelerometerRange> for RawScaling {
+    fn from(range: AccelerometerRange) -> Self {
+        RawScaling::from_full_scale(range.g(), 16)
+    }
+}
+
+/// Typical 16-bit magnetometer sensitivities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MagnetometerRange {
+    /// 0.15 µT per LSB, as used by the AK8963 and AK09916.
+    Ak09916,
+    /// ±4 gauss at 6842 LSB per gauss, as used by the LIS3MDL.
+    Gauss4,
+    /// ±8 gauss at 3421 LSB per gauss, as used by the LIS3MDL.
+    Gauss8,
+    /// ±12 gauss at 2281 LSB per gauss, as used by the LIS3MDL.
+    Gauss12,
+    /// ±16 gauss at 1711 LSB per gauss, as used by the LIS3MDL.
+    Gauss16,
+}
+
+impl MagnetometerRange {
+    /// Returns the sensitivity in LSB per µT.
+    pub fn lsb_per_microtesla(self) -> f32 {
+        match self {
+            MagnetometerRange::Ak09916 => 1.0 / 0.15,
+            MagnetometerRange::Gauss4 => 68.42,
+            MagnetometerRange::Gauss8 => 34.21,
+            MagnetometerRange::Gauss12 => 22.81,
+            MagnetometerRange::Gauss16 => 17.11,
+        }
+    }
+}
+
+impl From<MagnetometerRange> for RawScaling {
+    fn from(range: MagnetometerRange) -> Self {
+        RawScaling::from_sensitivity(range.lsb_per_microtesla())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gyroscope_preset_scales_counts() {
+        let scaling = RawScaling::from(GyroscopeRange::Dps2000);
+
+        // Act
+        let vector = scaling.apply([16384_i16, -32768, 0]);
+
+        assert_eq!(vector, Vector::new(1000.0, -2000.0, 0.0));
+        assert_eq!(scaling.full_scale(), 2000.0);
+        let full_range = RawScaling::from_full_scale(1.0, 32);
+        assert_eq!(
+            (full_range.minimum, full_range.maximum),
+            (i32::MIN, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn saturation_is_detected() {
+        let scaling = RawScaling::from(AccelerometerRange::G4);
+
+        // Act
+        let saturated = scaling.is_saturated([0_i16, i16::MAX, 0]);
+
+        assert!(saturated);
+        assert!(!scaling.is_saturated([100_i16, -100, 8192]));
+    }
+}
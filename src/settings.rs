@@ -4,6 +4,11 @@ use crate::math::Convention;
 
 /// AHRS algorithm settings.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SerdeSettings", into = "SerdeSettings")
+)]
 pub struct Settings {
     pub(crate) inner: sys::FusionAhrsSettings,
 }
@@ -23,12 +28,57 @@ impl Default for Settings {
     }
 }
 
+impl PartialEq for Settings {
+    fn eq(&self, other: &Self) -> bool {
+        self.convention() == other.convention()
+            && self.gain() == other.gain()
+            && self.gyroscope_range() == other.gyroscope_range()
+            && self.acceleration_rejection() == other.acceleration_rejection()
+            && self.magnetic_rejection() == other.magnetic_rejection()
+            && self.recovery_trigger_period() == other.recovery_trigger_period()
+    }
+}
+
 impl Settings {
     /// Create a new `Settings` instance.
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Returns the Earth axes convention.
+    pub fn convention(&self) -> Convention {
+        match self.inner.convention {
+            sys::FusionConvention_FusionConventionEnu => Convention::EastNorthUp,
+            sys::FusionConvention_FusionConventionNed => Convention::NorthWestDown,
+            _ => Convention::NorthWestUp,
+        }
+    }
+
+    /// Returns the AHRS algorithm gain.
+    pub fn gain(&self) -> f32 {
+        self.inner.gain
+    }
+
+    /// Returns the gyroscope range.
+    pub fn gyroscope_range(&self) -> f32 {
+        self.inner.gyroscopeRange
+    }
+
+    /// Returns the acceleration rejection.
+    pub fn acceleration_rejection(&self) -> f32 {
+        self.inner.accelerationRejection
+    }
+
+    /// Returns the magnetic rejection.
+    pub fn magnetic_rejection(&self) -> f32 {
+        self.inner.magneticRejection
+    }
+
+    /// Returns the recovery trigger period.
+    pub fn recovery_trigger_period(&self) -> u32 {
+        self.inner.recoveryTriggerPeriod
+    }
+
     /// Sets the Earth axes convention.
     pub fn set_convention(&mut self, convention: Convention) {
         self.inner.convention = convention as u32;
@@ -59,3 +109,51 @@ impl Settings {
         self.inner.recoveryTriggerPeriod = period;
     }
 }
+
+/// Serialised representation of [`Settings`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct SerdeSettings {
+    convention: Convention,
+    gain: f32,
+    gyroscope_range: f32,
+    acceleration_rejection: f32,
+    magnetic_rejection: f32,
+    recovery_trigger_period: u32,
+}
+
+#[cfg(feature = "serde")]
+impl Default for SerdeSettings {
+    fn default() -> Self {
+        Settings::default().into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Settings> for SerdeSettings {
+    fn from(settings: Settings) -> Self {
+        Self {
+            convention: settings.convention(),
+            gain: settings.gain(),
+            gyroscope_range: settings.gyroscope_range(),
+            acceleration_rejection: settings.acceleration_rejection(),
+            magnetic_rejection: settings.magnetic_rejection(),
+            recovery_trigger_period: settings.recovery_trigger_period(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SerdeSettings> for Settings {
+    fn from(settings: SerdeSettings) -> Self {
+        let mut result = Settings::new();
+        result.set_convention(settings.convention);
+        result.set_gain(settings.gain);
+        result.set_gyroscope_range(settings.gyroscope_range);
+        result.set_acceleration_rejection(settings.acceleration_rejection);
+        result.set_magnetic_rejection(settings.magnetic_rejection);
+        result.set_recovery_trigger_period(settings.recovery_trigger_period);
+        result
+    }
+}
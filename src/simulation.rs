@@ -0,0 +1,380 @@
+use crate::{Convention, Matrix, Quaternion, Vector};
+
+/// Segment of a scripted trajectory with constant motion.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Segment {
+    /// Duration in seconds.
+    pub duration: f32,
+    /// Angular rate in degrees per second, in the sensor frame.
+    pub angular_rate: Vector,
+    /// Linear acceleration in g, in the Earth frame.
+    pub acceleration: Vector,
+    /// Magnetic field added to the Earth's field, in the Earth frame.
+    pub magnetic_disturbance: Vector,
+}
+
+impl Segment {
+    /// Create a new `Segment` instance without motion.
+    pub fn stationary(duration: f32) -> Self {
+        Self {
+            duration,
+            ..Default::default()
+        }
+    }
+
+    /// Create a new `Segment` instance rotating at a constant angular rate in
+    /// degrees per second.
+    pub fn rotation(duration: f32, angular_rate: Vector) -> Self {
+        Self {
+            duration,
+            angular_rate,
+            ..Default::default()
+        }
+    }
+}
+
+/// Error model of a three-axis sensor.
+///
+/// The measurement is `misalignment * (sensitivity ⊙ truth) + bias + noise`,
+/// clamped to the range and rounded to the resolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SensorErrors {
+    /// White noise density in units per square root hertz.
+    pub white_noise: f32,
+    /// Initial bias.
+    pub bias: Vector,
+    /// Bias random walk in units per second per square root hertz.
+    pub bias_random_walk: f32,
+    /// Sensitivity (scale factor) of each axis.
+    pub sensitivity: Vector,
+    /// Misalignment matrix.
+    pub misalignment: Matrix,
+    /// Measurement range. Measurements saturate at ± range.
+    pub range: f32,
+    /// Quantisation step, or 0 for none.
+    pub resolution: f32,
+}
+
+impl Default for SensorErrors {
+    fn default() -> Self {
+        Self {
+            white_noise: 0.0,
+            bias: Vector::ZERO,
+            bias_random_walk: 0.0,
+            sensitivity: Vector::new(1.0, 1.0, 1.0),
+            misalignment: Matrix::IDENTITY,
+            range: f32::INFINITY,
+            resolution: 0.0,
+        }
+    }
+}
+
+/// Simulation configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SimulationConfig {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Earth axes convention.
+    pub convention: Convention,
+    /// Initial orientation of the sensor relative to the Earth.
+    pub initial_quaternion: Quaternion,
+    /// Earth's magnetic field in the Earth frame, in arbitrary units.
+    pub magnetic_field: Vector,
+    /// Gyroscope errors, in degrees per second. The range should match the
+    /// gyroscope range of the AHRS settings.
+    pub gyroscope: SensorErrors,
+    /// Accelerometer errors, in g.
+    pub accelerometer: SensorErrors,
+    /// Magnetometer errors, in the units of the magnetic field.
+    pub magnetometer: SensorErrors,
+    /// Soft-iron distortion matrix applied to the magnetic field in the
+    /// sensor frame.
+    pub soft_iron: Matrix,
+    /// Hard-iron offset added to the magnetic field in the sensor frame.
+    pub hard_iron: Vector,
+    /// Seed of the random number generator.
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 100,
+            convention: Convention::NorthWestUp,
+            initial_quaternion: Quaternion::IDENTITY,
+            magnetic_field: Vector::new(20.0, 0.0, -40.0),
+            gyroscope: SensorErrors::default(),
+            accelerometer: SensorErrors::default(),
+            magnetometer: SensorErrors::default(),
+            soft_iron: Matrix::IDENTITY,
+            hard_iron: Vector::ZERO,
+            seed: 0x2545f4914f6cdd1d,
+        }
+    }
+}
+
+/// Simulated sensor measurements with the true orientation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct SimulatedSample {
+    /// Time in seconds.
+    pub time: f32,
+    /// Gyroscope measurement in degrees per second.
+    pub gyroscope: Vector,
+    /// Accelerometer measurement in g.
+    pub accelerometer: Vector,
+    /// Magnetometer measurement in the units of the magnetic field.
+    pub magnetometer: Vector,
+    /// True orientation of the sensor relative to the Earth.
+    pub quaternion: Quaternion,
+}
+
+/// Generator of IMU samples from a scripted trajectory.
+///
+/// Iterating yields one sample per sample period until the end of the
+/// trajectory. The true quaternion of each sample is the orientation after
+/// rotating at the sample's angular rate for one sample period, matching an
+/// AHRS algorithm updated with that sample.
+pub struct Simulator<'a> {
+    config: SimulationConfig,
+    trajectory: &'a [Segment],
+    segment: usize,
+    remaining: u32,
+    count: u32,
+    quaternion: Quaternion,
+    gyroscope_bias: Vector,
+    accelerometer_bias: Vector,
+    magnetometer_bias: Vector,
+    random: Random,
+}
+
+impl<'a> Simulator<'a> {
+    /// Create a new `Simulator` instance.
+    pub fn new(config: SimulationConfig, trajectory: &'a [Segment]) -> Self {
+        let mut simulator = Self {
+            config,
+            trajectory,
+            segment: 0,
+            remaining: 0,
+            count: 0,
+            quaternion: config.initial_quaternion.normalise(),
+            gyroscope_bias: config.gyroscope.bias,
+            accelerometer_bias: config.accelerometer.bias,
+            magnetometer_bias: config.magnetometer.bias,
+            random: Random(config.seed | 1),
+        };
+        simulator.remaining = simulator.samples(0);
+        simulator
+    }
+
+    /// Returns the simulation configuration.
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    fn samples(&self, segment: usize) -> u32 {
+        self.trajectory.get(segment).map_or(0, |segment| {
+            libm::roundf(segment.duration * self.config.sample_rate as f32) as u32
+        })
+    }
+
+    fn measure(&mut self, truth: Vector, errors: SensorErrors, bias: Vector) -> Vector {
+        let sample_rate = self.config.sample_rate as f32;
+        let deviation = errors.white_noise * libm::sqrtf(sample_rate);
+        let noise = Vector::new(
+            self.random.normal(),
+            self.random.normal(),
+            self.random.normal(),
+        ) * deviation;
+        let measurement = errors.misalignment * Vector::hadamard_product(errors.sensitivity, truth)
+            + bias
+            + noise;
+        let finish = |value: f32| {
+            let value = value.clamp(-errors.range, errors.range);
+            if errors.resolution > 0.0 {
+                libm::roundf(value / errors.resolution) * errors.resolution
+            } else {
+                value
+            }
+        };
+        Vector::new(
+            finish(measurement.x),
+            finish(measurement.y),
+            finish(measurement.z),
+        )
+    }
+
+    fn walk(&mut self, bias: Vector, random_walk: f32) -> Vector {
+        let deviation = random_walk / libm::sqrtf(self.config.sample_rate as f32);
+        bias + Vector::new(
+            self.random.normal(),
+            self.random.normal(),
+            self.random.normal(),
+        ) * deviation
+    }
+}
+
+impl Iterator for Simulator<'_> {
+    type Item = SimulatedSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining == 0 {
+            if self.segment >= self.trajectory.len() {
+                return None;
+            }
+            self.segment += 1;
+            self.remaining = self.samples(self.segment);
+        }
+        self.remaining -= 1;
+        self.count += 1;
+        let segment = self.trajectory[self.segment];
+        let config = self.config;
+        let delta_time = 1.0 / config.sample_rate as f32;
+
+        // Rotate by the body-frame angular rate over one sample period
+        if !segment.angular_rate.is_zero() {
+            let angle = segment.angular_rate.magnitude() * delta_time;
+            let rotation = Quaternion::from_axis_angle(segment.angular_rate, angle);
+            self.quaternion = (self.quaternion * rotation).normalise();
+        }
+
+        // Transform Earth-frame vectors into the sensor frame
+        let to_sensor = self.quaternion.conjugate();
+        let up = match config.convention {
+            Convention::NorthWestUp | Convention::EastNorthUp => Vector::new(0.0, 0.0, 1.0),
+            Convention::NorthWestDown => Vector::new(0.0, 0.0, -1.0),
+        };
+        let specific_force = to_sensor.rotate(up + segment.acceleration);
+        let field = to_sensor.rotate(config.magnetic_field + segment.magnetic_disturbance);
+        let field = config.soft_iron * field + config.hard_iron;
+
+        self.gyroscope_bias = self.walk(self.gyroscope_bias, config.gyroscope.bias_random_walk);
+        self.accelerometer_bias = self.walk(
+            self.accelerometer_bias,
+            config.accelerometer.bias_random_walk,
+        );
+        self.magnetometer_bias =
+            self.walk(self.magnetometer_bias, config.magnetometer.bias_random_walk);
+        Some(SimulatedSample {
+            time: self.count as f32 * delta_time,
+            gyroscope: self.measure(segment.angular_rate, config.gyroscope, self.gyroscope_bias),
+            accelerometer: self.measure(
+                specific_force,
+                config.accelerometer,
+                self.accelerometer_bias,
+            ),
+            magnetometer: self.measure(field, config.magnetometer, self.magnetometer_bias),
+            quaternion: self.quaternion,
+        })
+    }
+}
+
+/// Deterministic random number generator.
+struct Random(u64);
+
+impl Random {
+    /// Returns a uniformly distributed number in [0, 1).
+    fn uniform(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a standard normally distributed number.
+    fn normal(&mut self) -> f32 {
+        let u = self.uniform().max(f32::MIN_POSITIVE);
+        let v = self.uniform();
+        libm::sqrtf(-2.0 * libm::logf(u)) * libm::cosf(2.0 * core::f32::consts::PI * v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FusionAhrs, Settings};
+
+    #[test]
+    fn ideal_sensors_track_true_orientation() {
+        let config = SimulationConfig::default();
+        let trajectory = [
+            Segment::stationary(5.0),
+            Segment::rotation(1.0, Vector::new(0.0, 0.0, 90.0)),
+            Segment::rotation(2.0, Vector::new(45.0, 0.0, 0.0)),
+            Segment::stationary(1.0),
+        ];
+        let mut ahrs = FusionAhrs::new();
+        ahrs.set_settings(Settings::new());
+        let mut last = None;
+
+        // Act
+        for sample in Simulator::new(config, &trajectory) {
+            ahrs.update(
+                sample.gyroscope,
+                sample.accelerometer,
+                sample.magnetometer,
+                0.01,
+            );
+            last = Some(sample);
+        }
+
+        let last = last.unwrap();
+        assert!((last.time - 9.0).abs() < 1e-3);
+        let euler = last.quaternion.to_euler();
+        assert!((euler.roll - 90.0).abs() < 0.1);
+        assert!((euler.yaw - 90.0).abs() < 0.1);
+        assert!(ahrs.get_quaternion().angle_to(last.quaternion) < 1.0);
+    }
+
+    #[test]
+    fn sensor_errors_are_applied() {
+        let config = SimulationConfig {
+            gyroscope: SensorErrors {
+                white_noise: 0.1,
+                bias: Vector::new(1.0, 0.0, 0.0),
+                range: 250.0,
+                ..Default::default()
+            },
+            accelerometer: SensorErrors {
+                resolution: 0.25,
+                sensitivity: Vector::new(1.0, 1.0, 1.1),
+                ..Default::default()
+            },
+            hard_iron: Vector::new(5.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let trajectory = [
+            Segment::stationary(10.0),
+            Segment::rotation(0.1, Vector::new(0.0, 0.0, 500.0)),
+        ];
+
+        let mut simulator = Simulator::new(config, &trajectory);
+        let first = simulator.next().unwrap();
+        let x = first.gyroscope.x;
+        let (mut sum, mut sum_squares) = (x, x * x);
+
+        // Act
+        for sample in simulator.by_ref().take(999) {
+            sum += sample.gyroscope.x;
+            sum_squares += sample.gyroscope.x * sample.gyroscope.x;
+        }
+        let rotating = simulator.nth(5).unwrap();
+
+        let mean = sum / 1000.0;
+        let variance = sum_squares / 1000.0 - mean * mean;
+        assert_eq!(simulator.count(), 4);
+        assert!((mean - 1.0).abs() < 0.1);
+        assert!((libm::sqrtf(variance) - 1.0).abs() < 0.1);
+        assert_eq!(first.accelerometer, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(first.magnetometer, Vector::new(25.0, 0.0, -40.0));
+        assert!(rotating.gyroscope.z <= 250.0);
+        assert!(rotating.gyroscope.z > 240.0);
+    }
+}
@@ -0,0 +1,424 @@
+use core::fmt;
+
+use crate::{InertialCalibration, Vector};
+
+/// Maximum polynomial order of the temperature model.
+pub const TEMPERATURE_MAX_ORDER: usize = 3;
+
+/// Temperature differences are divided by this scale while fitting to keep
+/// the normal equations well conditioned.
+const TEMPERATURE_SCALE: f64 = 10.0;
+
+/// Temperature compensation model for gyroscope or accelerometer
+/// measurements.
+///
+/// The uncalibrated measurement at temperature T is modelled per axis as
+/// `g(T) * m + o(T)`, where `m` is the measurement without temperature
+/// effects and, with `dT` the difference from the reference temperature:
+/// - `o(T) = offset[0] + offset[1] * dT + offset[2] * dT² + offset[3] * dT³`
+/// - `g(T) = 1 + sensitivity[0] * dT + sensitivity[1] * dT² + sensitivity[2] * dT³`
+///
+/// The default value applies no compensation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct TemperatureCompensation {
+    /// Reference temperature in degrees Celsius.
+    pub reference_temperature: f32,
+    /// Offset polynomial coefficients in ascending order of power.
+    pub offset: [Vector; TEMPERATURE_MAX_ORDER + 1],
+    /// Relative sensitivity polynomial coefficients in ascending order of
+    /// power, starting at the first power.
+    pub sensitivity: [Vector; TEMPERATURE_MAX_ORDER],
+}
+
+impl TemperatureCompensation {
+    /// Returns the offset at a temperature in degrees Celsius.
+    pub fn offset_at(&self, temperature: f32) -> Vector {
+        let delta = temperature - self.reference_temperature;
+        let mut power = 1.0;
+        let mut offset = Vector::ZERO;
+        for coefficient in self.offset {
+            offset += coefficient * power;
+            power *= delta;
+        }
+        offset
+    }
+
+    /// Returns the relative sensitivity at a temperature in degrees Celsius.
+    pub fn sensitivity_at(&self, temperature: f32) -> Vector {
+        let delta = temperature - self.reference_temperature;
+        let mut power = delta;
+        let mut sensitivity = Vector::ONES;
+        for coefficient in self.sensitivity {
+            sensitivity += coefficient * power;
+            power *= delta;
+        }
+        sensitivity
+    }
+
+    /// Removes the temperature effects from an uncalibrated measurement.
+    ///
+    /// Arguments:
+    /// - `uncalibrated`: Uncalibrated measurement.
+    /// - `temperature`: Sensor temperature in degrees Celsius.
+    pub fn apply(&self, uncalibrated: Vector, temperature: f32) -> Vector {
+        let sensitivity = self.sensitivity_at(temperature);
+        let corrected = uncalibrated - self.offset_at(temperature);
+        Vector::new(
+            corrected.x / sensitivity.x,
+            corrected.y / sensitivity.y,
+            corrected.z / sensitivity.z,
+        )
+    }
+
+    /// Returns the calibration adjusted for a temperature in degrees Celsius.
+    ///
+    /// Passing the result to [`calibration_inertial`](crate::calibration_inertial)
+    /// is equivalent to calibrating the output of [`apply`](Self::apply) with
+    /// the unadjusted calibration. If the offset polynomial models the full
+    /// sensor offset, as it does when a gyroscope is fitted against zero, the
+    /// offset of the calibration should be zero.
+    pub fn compensate(
+        &self,
+        calibration: InertialCalibration,
+        temperature: f32,
+    ) -> InertialCalibration {
+        let sensitivity = self.sensitivity_at(temperature);
+        InertialCalibration {
+            misalignment: calibration.misalignment,
+            sensitivity: Vector::new(
+                calibration.sensitivity.x / sensitivity.x,
+                calibration.sensitivity.y / sensitivity.y,
+                calibration.sensitivity.z / sensitivity.z,
+            ),
+            offset: self.offset_at(temperature) + calibration.offset.hadamard_product(sensitivity),
+        }
+    }
+}
+
+/// Number of parameters of the temperature model per axis.
+const PARAMETER_COUNT: usize = 2 * TEMPERATURE_MAX_ORDER + 1;
+
+/// Least squares fitter for [`TemperatureCompensation`].
+///
+/// Samples are static measurements logged over a range of temperatures. The
+/// sensitivity polynomial is only fitted if the reference measurements vary
+/// at each temperature, for example an accelerometer logged in both the +1 g
+/// and -1 g orientation of each axis. Otherwise only the offset is fitted.
+#[derive(Debug, Clone)]
+pub struct TemperatureCompensationFitter {
+    reference_temperature: f32,
+    order: usize,
+    normal: [[[f64; PARAMETER_COUNT]; PARAMETER_COUNT]; 3],
+    right: [[f64; PARAMETER_COUNT]; 3],
+    minimum_temperature: f32,
+    maximum_temperature: f32,
+    count: u32,
+}
+
+impl TemperatureCompensationFitter {
+    /// Create a new `TemperatureCompensationFitter` instance.
+    ///
+    /// Arguments:
+    /// - `reference_temperature`: Reference temperature in degrees Celsius.
+    /// - `order`: Polynomial order, clamped to between 1 and
+    ///   [`TEMPERATURE_MAX_ORDER`].
+    pub fn new(reference_temperature: f32, order: usize) -> Self {
+        Self {
+            reference_temperature,
+            order: order.clamp(1, TEMPERATURE_MAX_ORDER),
+            normal: [[[0.0; PARAMETER_COUNT]; PARAMETER_COUNT]; 3],
+            right: [[0.0; PARAMETER_COUNT]; 3],
+            minimum_temperature: f32::INFINITY,
+            maximum_temperature: f32::NEG_INFINITY,
+            count: 0,
+        }
+    }
+
+    /// Adds a static measurement whose value without temperature effects is
+    /// zero, such as a gyroscope measurement.
+    pub fn add_sample(&mut self, temperature: f32, reading: Vector) {
+        self.add_sample_with_reference(temperature, reading, Vector::ZERO);
+    }
+
+    /// Adds a static measurement and its value without temperature effects,
+    /// such as the accelerometer measurement at the reference temperature in
+    /// the same orientation.
+    pub fn add_sample_with_reference(
+        &mut self,
+        temperature: f32,
+        reading: Vector,
+        reference: Vector,
+    ) {
+        let delta = (temperature - self.reference_temperature) as f64 / TEMPERATURE_SCALE;
+        let readings = [reading.x, reading.y, reading.z];
+        let references = [reference.x, reference.y, reference.z];
+        for axis in 0..3 {
+            let reference = references[axis] as f64;
+            let regressors = self.regressors(delta, reference);
+            let measurement = readings[axis] as f64 - reference;
+            for (row, &regressor) in self.normal[axis].iter_mut().zip(&regressors) {
+                for (element, &other) in row.iter_mut().zip(&regressors) {
+                    *element += regressor * other;
+                }
+            }
+            for (element, &regressor) in self.right[axis].iter_mut().zip(&regressors) {
+                *element += regressor * measurement;
+            }
+        }
+        self.minimum_temperature = self.minimum_temperature.min(temperature);
+        self.maximum_temperature = self.maximum_temperature.max(temperature);
+        self.count += 1;
+    }
+
+    /// Returns the regressors: powers of the temperature for the offset,
+    /// followed by the reference scaled by powers of the temperature for the
+    /// sensitivity.
+    fn regressors(&self, delta: f64, reference: f64) -> [f64; PARAMETER_COUNT] {
+        let mut regressors = [0.0; PARAMETER_COUNT];
+        let mut power = 1.0;
+        for index in 0..=self.order {
+            regressors[index] = power;
+            if index > 0 {
+                regressors[TEMPERATURE_MAX_ORDER + index] = reference * power;
+            }
+            power *= delta;
+        }
+        regressors
+    }
+
+    /// Fits the temperature compensation model to the samples.
+    pub fn fit(&self) -> Result<TemperatureCompensation, TemperatureFitError> {
+        if self.count <= self.order as u32 || self.maximum_temperature <= self.minimum_temperature {
+            return Err(TemperatureFitError::InsufficientSamples);
+        }
+        let mut compensation = TemperatureCompensation {
+            reference_temperature: self.reference_temperature,
+            ..Default::default()
+        };
+        for axis in 0..3 {
+            let mut parameters: [usize; PARAMETER_COUNT] = [0; PARAMETER_COUNT];
+            let offset_count = self.order + 1;
+            for (index, parameter) in parameters.iter_mut().enumerate().take(offset_count) {
+                *parameter = index;
+            }
+            for index in 1..=self.order {
+                parameters[offset_count + index - 1] = TEMPERATURE_MAX_ORDER + index;
+            }
+            let solution = solve(
+                &self.normal[axis],
+                &self.right[axis],
+                &parameters[..2 * self.order + 1],
+            )
+            .or_else(|| {
+                solve(
+                    &self.normal[axis],
+                    &self.right[axis],
+                    &parameters[..offset_count],
+                )
+            })
+            .ok_or(TemperatureFitError::Singular)?;
+
+            let mut scale = 1.0;
+            for power in 0..=TEMPERATURE_MAX_ORDER {
+                let offset = (solution[power] / scale) as f32;
+                set_axis(&mut compensation.offset[power], axis, offset);
+                if power > 0 {
+                    let sensitivity = (solution[TEMPERATURE_MAX_ORDER + power] / scale) as f32;
+                    set_axis(&mut compensation.sensitivity[power - 1], axis, sensitivity);
+                }
+                scale *= TEMPERATURE_SCALE;
+            }
+        }
+        Ok(compensation)
+    }
+}
+
+fn set_axis(vector: &mut Vector, axis: usize, value: f32) {
+    match axis {
+        0 => vector.x = value,
+        1 => vector.y = value,
+        _ => vector.z = value,
+    }
+}
+
+/// Solves the normal equations restricted to `parameters` using Gaussian
+/// elimination with partial pivoting. Returns `None` if the system is
+/// singular.
+fn solve(
+    normal: &[[f64; PARAMETER_COUNT]; PARAMETER_COUNT],
+    right: &[f64; PARAMETER_COUNT],
+    parameters: &[usize],
+) -> Option<[f64; PARAMETER_COUNT]> {
+    let count = parameters.len();
+    let mut a = [[0.0; PARAMETER_COUNT + 1]; PARAMETER_COUNT];
+    let mut largest: f64 = 0.0;
+    for (row, &i) in parameters.iter().enumerate() {
+        for (column, &j) in parameters.iter().enumerate() {
+            a[row][column] = normal[i][j];
+        }
+        a[row][count] = right[i];
+        largest = largest.max(normal[i][i]);
+    }
+    if largest <= 0.0 {
+        return None;
+    }
+
+    for column in 0..count {
+        let pivot = (column..count)
+            .max_by(|&i, &j| libm::fabs(a[i][column]).total_cmp(&libm::fabs(a[j][column])))?;
+        if libm::fabs(a[pivot][column]) <= 1e-9 * largest {
+            return None;
+        }
+        a.swap(column, pivot);
+        for row in column + 1..count {
+            let factor = a[row][column] / a[column][column];
+            let pivot_row = a[column];
+            for (element, pivot_element) in a[row][column..=count]
+                .iter_mut()
+                .zip(&pivot_row[column..=count])
+            {
+                *element -= factor * pivot_element;
+            }
+        }
+    }
+
+    let mut solution = [0.0; PARAMETER_COUNT];
+    for row in (0..count).rev() {
+        let mut value = a[row][count];
+        for column in row + 1..count {
+            value -= a[row][column] * solution[parameters[column]];
+        }
+        solution[parameters[row]] = value / a[row][row];
+    }
+    Some(solution)
+}
+
+/// Temperature compensation fit error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum TemperatureFitError {
+    /// Not enough samples or temperatures to fit the polynomial order.
+    InsufficientSamples,
+    /// The samples don't constrain the model.
+    Singular,
+}
+
+impl fmt::Display for TemperatureFitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientSamples => write!(f, "insufficient samples"),
+            Self::Singular => write!(f, "samples don't constrain the model"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperatures() -> impl Iterator<Item = f32> {
+        (0..=80).map(|index| -20.0 + index as f32)
+    }
+
+    #[test]
+    fn gyroscope_offset_is_fitted() {
+        let expected = TemperatureCompensation {
+            reference_temperature: 25.0,
+            offset: [
+                Vector::new(0.5, -0.2, 0.1),
+                Vector::new(0.02, 0.01, -0.03),
+                Vector::new(0.001, -0.0005, 0.0002),
+                Vector::ZERO,
+            ],
+            sensitivity: [Vector::ZERO; TEMPERATURE_MAX_ORDER],
+        };
+        let mut fitter = TemperatureCompensationFitter::new(25.0, 2);
+        for temperature in temperatures() {
+            fitter.add_sample(temperature, expected.offset_at(temperature));
+        }
+
+        // Act
+        let compensation = fitter.fit().unwrap();
+
+        for temperature in temperatures() {
+            let error = compensation.offset_at(temperature) - expected.offset_at(temperature);
+            assert!(error.magnitude() < 1e-4);
+        }
+        assert_eq!(compensation.sensitivity_at(60.0), Vector::ONES);
+    }
+
+    #[test]
+    fn accelerometer_sensitivity_is_fitted() {
+        let expected = TemperatureCompensation {
+            reference_temperature: 25.0,
+            offset: [
+                Vector::ZERO,
+                Vector::new(0.0002, -0.0001, 0.0003),
+                Vector::ZERO,
+                Vector::ZERO,
+            ],
+            sensitivity: [
+                Vector::new(0.0001, 0.0002, -0.0001),
+                Vector::ZERO,
+                Vector::ZERO,
+            ],
+        };
+        let mut fitter = TemperatureCompensationFitter::new(25.0, 1);
+        for temperature in temperatures() {
+            for reference in [Vector::ONES, -Vector::ONES] {
+                let sensitivity = expected.sensitivity_at(temperature);
+                let reading =
+                    reference.hadamard_product(sensitivity) + expected.offset_at(temperature);
+                fitter.add_sample_with_reference(temperature, reading, reference);
+            }
+        }
+
+        // Act
+        let compensation = fitter.fit().unwrap();
+
+        let reading = Vector::new(0.01, -0.98, 0.2);
+        let error = compensation.apply(reading, 55.0) - expected.apply(reading, 55.0);
+        assert!(error.magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn compensated_calibration_matches_applied_compensation() {
+        let compensation = TemperatureCompensation {
+            reference_temperature: 20.0,
+            offset: [
+                Vector::new(0.1, 0.2, 0.3),
+                Vector::ONES * 0.01,
+                Vector::ZERO,
+                Vector::ZERO,
+            ],
+            sensitivity: [Vector::ONES * 0.001, Vector::ZERO, Vector::ZERO],
+        };
+        let calibration = InertialCalibration {
+            sensitivity: Vector::new(1.1, 0.9, 1.0),
+            offset: Vector::new(0.05, -0.05, 0.0),
+            ..Default::default()
+        };
+        let reading = Vector::new(1.0, 2.0, 3.0);
+
+        // Act
+        let compensated = compensation.compensate(calibration, 40.0).apply(reading);
+
+        let expected = calibration.apply(compensation.apply(reading, 40.0));
+        assert!((compensated - expected).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn single_temperature_is_rejected() {
+        let mut fitter = TemperatureCompensationFitter::new(25.0, 1);
+        for _ in 0..10 {
+            fitter.add_sample(25.0, Vector::ONES);
+        }
+
+        // Act
+        let result = fitter.fit();
+
+        assert_eq!(result, Err(TemperatureFitError::InsufficientSamples));
+    }
+}
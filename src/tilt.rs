@@ -0,0 +1,59 @@
+use crate::{Convention, Vector};
+
+/// Tilt of the sensor relative to level, independent of heading.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Tilt {
+    /// Angle in degrees between the sensor vertical axis and the Earth
+    /// vertical, from 0 (level) to 180 (upside down).
+    pub inclination: f32,
+    /// Direction in degrees of the tilt in the sensor x-y plane, measured
+    /// from the x axis towards the y axis. This is the direction that points
+    /// most upwards. Zero when level.
+    pub azimuth: f32,
+}
+
+impl Tilt {
+    /// Create a new `Tilt` instance from the gravity measurement in the
+    /// sensor frame.
+    ///
+    /// Arguments:
+    /// - `gravity`: Accelerometer measurement of gravity, as returned by
+    ///   [`FusionAhrs::get_gravity`](crate::FusionAhrs::get_gravity).
+    /// - `convention`: Earth axes convention, which sets the sensor vertical
+    ///   axis.
+    pub fn from_gravity(gravity: Vector, convention: Convention) -> Self {
+        let gravity = gravity.normalise();
+        let vertical = match convention {
+            Convention::NorthWestDown => -gravity.z,
+            _ => gravity.z,
+        };
+        let horizontal = libm::sqrtf(gravity.x * gravity.x + gravity.y * gravity.y);
+        Self {
+            inclination: libm::atan2f(horizontal, vertical).to_degrees(),
+            azimuth: libm::atan2f(gravity.y, gravity.x).to_degrees(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Axis, Quaternion};
+
+    #[test]
+    fn pitched_sensor_tilts_along_x() {
+        let quaternion = Quaternion::from_axis_angle(Axis::Y.unit_vector(), -20.0)
+            * Quaternion::from_axis_angle(Axis::Z.unit_vector(), 123.0);
+        let gravity = quaternion.to_matrix().rows()[2];
+
+        // Act
+        let tilt = Tilt::from_gravity(gravity, Convention::NorthWestUp);
+
+        assert!((tilt.inclination - 20.0).abs() < 1e-3);
+        assert!((tilt.azimuth + 123.0).abs() < 1e-3);
+        let level = Tilt::from_gravity(Vector::new(0.0, 0.0, -1.0), Convention::NorthWestDown);
+        assert_eq!(level, Tilt::default());
+    }
+}
@@ -0,0 +1,515 @@
+use std::thread;
+use std::vec::Vec;
+
+use crate::{Benchmark, BenchmarkConfig, BenchmarkReport, FusionAhrs, Settings, TruthSample};
+
+/// Error metric minimised by the tuner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Metric {
+    /// RMS geodesic attitude error.
+    #[default]
+    AttitudeRms,
+    /// Maximum geodesic attitude error.
+    AttitudeMaximum,
+    /// RMS heading error.
+    HeadingRms,
+    /// Time from the start until the attitude error first falls below the
+    /// convergence threshold.
+    StartConvergence,
+}
+
+impl Metric {
+    /// Returns the value of the metric, where lower is better.
+    pub fn evaluate(&self, report: &BenchmarkReport) -> f32 {
+        let value = match self {
+            Self::AttitudeRms => report.attitude.rms,
+            Self::AttitudeMaximum => report.attitude.maximum,
+            Self::HeadingRms => report.heading.rms,
+            Self::StartConvergence => report.start_convergence.unwrap_or(f32::INFINITY),
+        };
+        if value.is_nan() {
+            f32::INFINITY
+        } else {
+            value
+        }
+    }
+}
+
+/// Tunable AHRS algorithm setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parameter {
+    /// Gain.
+    Gain,
+    /// Acceleration rejection in degrees.
+    AccelerationRejection,
+    /// Magnetic rejection in degrees.
+    MagneticRejection,
+    /// Recovery trigger period in samples.
+    RecoveryTriggerPeriod,
+}
+
+impl Parameter {
+    /// Returns the value of the parameter.
+    pub fn get(&self, settings: &Settings) -> f32 {
+        match self {
+            Self::Gain => settings.gain(),
+            Self::AccelerationRejection => settings.acceleration_rejection(),
+            Self::MagneticRejection => settings.magnetic_rejection(),
+            Self::RecoveryTriggerPeriod => settings.recovery_trigger_period() as f32,
+        }
+    }
+
+    /// Sets the value of the parameter. The recovery trigger period is
+    /// rounded to a whole number of samples.
+    pub fn set(&self, settings: &mut Settings, value: f32) {
+        match self {
+            Self::Gain => settings.set_gain(value),
+            Self::AccelerationRejection => settings.set_acceleration_rejection(value),
+            Self::MagneticRejection => settings.set_magnetic_rejection(value),
+            Self::RecoveryTriggerPeriod => {
+                settings.set_recovery_trigger_period(value.max(0.0).round() as u32)
+            }
+        }
+    }
+}
+
+/// Evenly spaced values of a parameter to search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterRange {
+    /// Parameter.
+    pub parameter: Parameter,
+    /// Minimum value.
+    pub minimum: f32,
+    /// Maximum value.
+    pub maximum: f32,
+    /// Number of values, including the minimum and maximum.
+    pub steps: usize,
+}
+
+impl ParameterRange {
+    /// Create a new `ParameterRange` instance.
+    pub fn new(parameter: Parameter, minimum: f32, maximum: f32, steps: usize) -> Self {
+        Self {
+            parameter,
+            minimum,
+            maximum,
+            steps,
+        }
+    }
+
+    /// Returns the values of the range.
+    pub fn values(&self) -> Vec<f32> {
+        if self.steps < 2 {
+            return Vec::from([self.minimum]);
+        }
+        let step = (self.maximum - self.minimum) / (self.steps - 1) as f32;
+        (0..self.steps)
+            .map(|index| self.minimum + index as f32 * step)
+            .collect()
+    }
+}
+
+/// Search strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Search {
+    /// Evaluates every combination of the parameter values.
+    Grid,
+    /// Optimises one parameter at a time over its values, starting from the
+    /// benchmark settings, until no parameter changes or the number of
+    /// rounds is reached.
+    CoordinateDescent {
+        /// Maximum number of passes over all parameters.
+        rounds: usize,
+    },
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self::CoordinateDescent { rounds: 3 }
+    }
+}
+
+/// Score of a parameter value with the other parameters at their best
+/// values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensitivityPoint {
+    /// Parameter value.
+    pub value: f32,
+    /// Metric value.
+    pub score: f32,
+}
+
+/// Sensitivity of the metric to one parameter.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sensitivity {
+    /// Parameter.
+    pub parameter: Parameter,
+    /// Scores over the parameter range.
+    pub points: Vec<SensitivityPoint>,
+}
+
+impl Sensitivity {
+    /// Returns the difference between the worst and best finite scores.
+    pub fn spread(&self) -> f32 {
+        let scores = self.points.iter().map(|point| point.score);
+        let finite = || scores.clone().filter(|score| score.is_finite());
+        let maximum = finite().fold(f32::NEG_INFINITY, f32::max);
+        let minimum = finite().fold(f32::INFINITY, f32::min);
+        (maximum - minimum).max(0.0)
+    }
+}
+
+/// Result of a tuning run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuningResult {
+    /// Best settings found.
+    pub settings: Settings,
+    /// Metric value of the best settings.
+    pub score: f32,
+    /// Number of benchmark evaluations.
+    pub evaluations: usize,
+    /// Sensitivity of the metric to each parameter around the best settings.
+    pub sensitivity: Vec<Sensitivity>,
+}
+
+/// Tuner of AHRS algorithm settings against a dataset with ground truth.
+///
+/// Candidate settings are benchmarked in parallel across threads.
+pub struct Tuner<'a> {
+    dataset: &'a [TruthSample],
+    config: BenchmarkConfig,
+    metric: Metric,
+    parameters: Vec<ParameterRange>,
+    search: Search,
+    threads: usize,
+}
+
+impl<'a> Tuner<'a> {
+    /// Create a new `Tuner` instance that tunes all parameters over typical
+    /// ranges by coordinate descent on the RMS attitude error, using one
+    /// thread per available core.
+    ///
+    /// Arguments:
+    /// - `dataset`: Samples with the true orientation.
+    /// - `config`: Benchmark configuration with the initial settings.
+    pub fn new(dataset: &'a [TruthSample], config: BenchmarkConfig) -> Self {
+        let period = 5 * config.sample_rate as usize;
+        Self {
+            dataset,
+            config,
+            metric: Metric::default(),
+            parameters: Vec::from([
+                ParameterRange::new(Parameter::Gain, 0.1, 1.0, 10),
+                ParameterRange::new(Parameter::AccelerationRejection, 0.0, 20.0, 5),
+                ParameterRange::new(Parameter::MagneticRejection, 0.0, 40.0, 5),
+                ParameterRange::new(Parameter::RecoveryTriggerPeriod, 0.0, period as f32, 6),
+            ]),
+            search: Search::default(),
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+
+    /// Sets the metric to minimise.
+    pub fn with_metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Sets the range of a parameter, replacing any previous range of the
+    /// same parameter.
+    pub fn with_parameter(mut self, range: ParameterRange) -> Self {
+        self.parameters
+            .retain(|existing| existing.parameter != range.parameter);
+        self.parameters.push(range);
+        self
+    }
+
+    /// Sets the parameters to tune, removing all others.
+    pub fn with_parameters(mut self, ranges: &[ParameterRange]) -> Self {
+        self.parameters = ranges.to_vec();
+        self
+    }
+
+    /// Sets the search strategy.
+    pub fn with_search(mut self, search: Search) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Sets the number of threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Benchmarks settings and returns the metric value.
+    pub fn evaluate(&self, settings: Settings) -> f32 {
+        let config = BenchmarkConfig {
+            settings,
+            ..self.config
+        };
+        self.metric
+            .evaluate(&Benchmark::run(config, self.dataset.iter().copied()))
+    }
+
+    /// Runs the search and the sensitivity analysis.
+    pub fn run(&self) -> TuningResult {
+        let mut evaluations = 0;
+        let (settings, score) = match self.search {
+            Search::Grid => {
+                let mut candidates = Vec::from([self.config.settings]);
+                for range in &self.parameters {
+                    candidates = candidates
+                        .iter()
+                        .flat_map(|settings| self.candidates(settings, range))
+                        .collect();
+                }
+                let scores = self.evaluate_all(&candidates);
+                evaluations += candidates.len();
+                best(&candidates, &scores).unwrap_or((self.config.settings, f32::INFINITY))
+            }
+            Search::CoordinateDescent { rounds } => {
+                let mut settings = self.config.settings;
+                let mut score = self.evaluate(settings);
+                evaluations += 1;
+                for _ in 0..rounds {
+                    let mut changed = false;
+                    for range in &self.parameters {
+                        let candidates = self.candidates(&settings, range);
+                        let scores = self.evaluate_all(&candidates);
+                        evaluations += candidates.len();
+                        if let Some((candidate, candidate_score)) = best(&candidates, &scores) {
+                            if candidate_score < score {
+                                settings = candidate;
+                                score = candidate_score;
+                                changed = true;
+                            }
+                        }
+                    }
+                    if !changed {
+                        break;
+                    }
+                }
+                (settings, score)
+            }
+        };
+
+        let sensitivity = self
+            .parameters
+            .iter()
+            .map(|range| {
+                let candidates = self.candidates(&settings, range);
+                let scores = self.evaluate_all(&candidates);
+                evaluations += candidates.len();
+                Sensitivity {
+                    parameter: range.parameter,
+                    points: candidates
+                        .iter()
+                        .zip(scores)
+                        .map(|(candidate, score)| SensitivityPoint {
+                            value: range.parameter.get(candidate),
+                            score,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        TuningResult {
+            settings,
+            score,
+            evaluations,
+            sensitivity,
+        }
+    }
+
+    /// Returns the settings with each value of the range.
+    fn candidates(&self, settings: &Settings, range: &ParameterRange) -> Vec<Settings> {
+        range
+            .values()
+            .into_iter()
+            .map(|value| {
+                let mut candidate = *settings;
+                range.parameter.set(&mut candidate, value);
+                candidate
+            })
+            .collect()
+    }
+
+    /// Evaluates the candidates across threads, preserving their order.
+    fn evaluate_all(&self, candidates: &[Settings]) -> Vec<f32> {
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+        let chunk_size = candidates.len().div_ceil(self.threads);
+        thread::scope(|scope| {
+            let handles: Vec<_> = candidates
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|settings| self.evaluate(*settings))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("benchmark thread panicked"))
+                .collect()
+        })
+    }
+}
+
+/// Returns the candidate with the lowest score.
+fn best(candidates: &[Settings], scores: &[f32]) -> Option<(Settings, f32)> {
+    candidates
+        .iter()
+        .zip(scores)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(settings, score)| (*settings, *score))
+}
+
+/// Returns the dataset of a stationary recording with the true orientation
+/// taken as the final orientation of an AHRS algorithm run over it twice.
+///
+/// Arguments:
+/// - `samples`: Stationary samples. The quaternions are ignored.
+/// - `settings`: AHRS algorithm settings used to estimate the orientation.
+/// - `sample_rate`: Sample rate in Hz.
+pub fn stationary_truth(
+    samples: &[TruthSample],
+    settings: Settings,
+    sample_rate: u32,
+) -> Vec<TruthSample> {
+    let mut ahrs = FusionAhrs::new();
+    ahrs.set_settings(settings);
+    let delta_time = 1.0 / sample_rate as f32;
+    for sample in samples.iter().chain(samples) {
+        match sample.magnetometer {
+            Some(magnetometer) => ahrs.update(
+                sample.gyroscope,
+                sample.accelerometer,
+                magnetometer,
+                delta_time,
+            ),
+            None => ahrs.update_no_magnetometer(sample.gyroscope, sample.accelerometer, delta_time),
+        }
+    }
+    let quaternion = ahrs.get_quaternion();
+    samples
+        .iter()
+        .map(|sample| TruthSample {
+            quaternion,
+            ..*sample
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Segment, SensorErrors, SimulationConfig, Simulator, Vector};
+
+    fn dataset() -> Vec<TruthSample> {
+        let config = SimulationConfig {
+            gyroscope: SensorErrors {
+                white_noise: 0.05,
+                bias: Vector::new(0.5, -0.5, 0.2),
+                ..Default::default()
+            },
+            accelerometer: SensorErrors {
+                white_noise: 0.002,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let trajectory = [
+            Segment::stationary(4.0),
+            Segment::rotation(2.0, Vector::new(30.0, 0.0, 20.0)),
+            Segment::stationary(4.0),
+        ];
+        Simulator::new(config, &trajectory)
+            .map(Into::into)
+            .collect()
+    }
+
+    #[test]
+    fn grid_search_improves_on_low_gain() {
+        let dataset = dataset();
+        let mut settings = Settings::new();
+        settings.set_gain(0.01);
+        let config = BenchmarkConfig {
+            settings,
+            offset_correction: false,
+            ..Default::default()
+        };
+        let tuner = Tuner::new(&dataset, config)
+            .with_parameters(&[ParameterRange::new(Parameter::Gain, 0.1, 1.0, 4)])
+            .with_parameter(ParameterRange::new(
+                Parameter::AccelerationRejection,
+                5.0,
+                10.0,
+                2,
+            ))
+            .with_search(Search::Grid)
+            .with_threads(3);
+        let initial = tuner.evaluate(settings);
+
+        // Act
+        let result = tuner.run();
+
+        assert!(result.score < initial);
+        assert_eq!(result.score, tuner.evaluate(result.settings));
+        assert_eq!(result.evaluations, 8 + 4 + 2);
+        assert_eq!(result.sensitivity.len(), 2);
+        assert_eq!(result.sensitivity[0].points.len(), 4);
+        assert!(result.sensitivity[0].spread() >= 0.0);
+    }
+
+    #[test]
+    fn coordinate_descent_matches_serial_evaluation() {
+        let dataset = dataset();
+        let config = BenchmarkConfig {
+            offset_correction: false,
+            ..Default::default()
+        };
+        let ranges = [
+            ParameterRange::new(Parameter::Gain, 0.1, 1.0, 5),
+            ParameterRange::new(Parameter::RecoveryTriggerPeriod, 0.0, 500.0, 3),
+        ];
+        let tuner = Tuner::new(&dataset, config).with_parameters(&ranges);
+
+        // Act
+        let parallel = tuner.run();
+        let serial = Tuner::new(&dataset, config)
+            .with_parameters(&ranges)
+            .with_threads(1)
+            .run();
+
+        assert_eq!(parallel, serial);
+        assert!(parallel.score <= tuner.evaluate(config.settings));
+    }
+
+    #[test]
+    fn stationary_truth_uses_final_orientation() {
+        let samples: Vec<TruthSample> = dataset().into_iter().take(400).collect();
+
+        // Act
+        let truth = stationary_truth(&samples, Settings::new(), 100);
+
+        assert_eq!(truth.len(), 400);
+        assert!(truth
+            .iter()
+            .all(|sample| sample.quaternion == truth[0].quaternion));
+        assert!(truth[0].quaternion.angle_to(samples[0].quaternion) < 2.0);
+    }
+}